@@ -19,10 +19,10 @@ static TEMPLATE: &str = r#"
 
 ## What this example demonstrates
 
-* read a skin from the skin file in the example directory
-* print this text with the skin just deserialized
-* a simple template
-* ~~animated style transitions~~
+* [x] read a skin from the skin file in the example directory
+* [x] print this text with the skin just deserialized
+* [x] a simple template
+* [ ] ~~animated style transitions~~
 
 ## How it works
 
@@ -33,7 +33,8 @@ let hjson = fs::read_to_string(file_path)?;
 let skin: MadSkin = deser_hjson::from_str(&hjson)?;
 ```
 
-*(of course it doesn't have to be Hjson, it can be JSON, TOML, or any serde compatible format of your choice)*
+> [!TIP]
+> It doesn't have to be Hjson, it can be JSON, TOML, or any serde compatible format of your choice.
 
 As we want to print the real skin file, we use a template (see the complete code), but using the skin could have been as simple as
 
@@ -0,0 +1,219 @@
+use {
+    crate::{
+        ansi,
+        crossterm::style::{
+            Attribute,
+            Color,
+        },
+        line::FmtLine,
+        ColorDepth,
+        CompositeKind,
+        CompoundStyle,
+        MadSkin,
+        StyledChar,
+    },
+    minimad::Compound,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// The kind of a GitHub style admonition, a quote starting
+/// with a line like `> [!NOTE]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdmonitionKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AdmonitionKind {
+    /// Parse the tag of the first line of the admonition, eg `"[!TIP]"`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let name = tag.trim().strip_prefix("[!")?.strip_suffix(']')?;
+        match name.to_ascii_uppercase().as_str() {
+            "NOTE" => Some(Self::Note),
+            "TIP" => Some(Self::Tip),
+            "IMPORTANT" => Some(Self::Important),
+            "WARNING" => Some(Self::Warning),
+            "CAUTION" => Some(Self::Caution),
+            _ => None,
+        }
+    }
+    /// Return the title displayed in place of the tag
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Tip => "Tip",
+            Self::Important => "Important",
+            Self::Warning => "Warning",
+            Self::Caution => "Caution",
+        }
+    }
+}
+
+/// The style of a kind of admonition
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdmonitionStyle {
+    /// replaces the quote mark on all lines of the admonition
+    pub mark: StyledChar,
+    /// applied over the paragraph style to the title
+    pub title: CompoundStyle,
+    /// applied over the paragraph style to the content
+    pub text: CompoundStyle,
+}
+
+impl Default for AdmonitionStyle {
+    fn default() -> Self {
+        Self {
+            mark: StyledChar::nude('▐'),
+            title: CompoundStyle::default(),
+            text: CompoundStyle::default(),
+        }
+    }
+}
+
+impl AdmonitionStyle {
+    pub fn with_color(color: Color) -> Self {
+        Self {
+            mark: StyledChar::new(CompoundStyle::with_fg(color), '▐'),
+            title: CompoundStyle::new(Some(color), None, Attribute::Bold.into()),
+            text: CompoundStyle::default(),
+        }
+    }
+    pub fn blend_with<C: Into<coolor::Color> + Copy>(&mut self, color: C, weight: f32) {
+        self.mark.blend_with(color, weight);
+        self.title.blend_with(color, weight);
+        self.text.blend_with(color, weight);
+    }
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        self.mark.limit_to_color_depth(depth);
+        self.title.limit_to_color_depth(depth);
+        self.text.limit_to_color_depth(depth);
+    }
+}
+
+/// The styles of the admonitions, per kind
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdmonitionStyles {
+    pub note: AdmonitionStyle,
+    pub tip: AdmonitionStyle,
+    pub important: AdmonitionStyle,
+    pub warning: AdmonitionStyle,
+    pub caution: AdmonitionStyle,
+}
+
+impl Default for AdmonitionStyles {
+    fn default() -> Self {
+        Self {
+            note: AdmonitionStyle::with_color(ansi(33)),
+            tip: AdmonitionStyle::with_color(ansi(35)),
+            important: AdmonitionStyle::with_color(ansi(135)),
+            warning: AdmonitionStyle::with_color(ansi(178)),
+            caution: AdmonitionStyle::with_color(ansi(160)),
+        }
+    }
+}
+
+impl AdmonitionStyles {
+    /// Build styles which don't change anything to the quote style
+    pub fn no_style() -> Self {
+        Self {
+            note: AdmonitionStyle::default(),
+            tip: AdmonitionStyle::default(),
+            important: AdmonitionStyle::default(),
+            warning: AdmonitionStyle::default(),
+            caution: AdmonitionStyle::default(),
+        }
+    }
+    pub fn style(&self, kind: AdmonitionKind) -> &AdmonitionStyle {
+        match kind {
+            AdmonitionKind::Note => &self.note,
+            AdmonitionKind::Tip => &self.tip,
+            AdmonitionKind::Important => &self.important,
+            AdmonitionKind::Warning => &self.warning,
+            AdmonitionKind::Caution => &self.caution,
+        }
+    }
+    fn styles_mut(&mut self) -> [&mut AdmonitionStyle; 5] {
+        [
+            &mut self.note,
+            &mut self.tip,
+            &mut self.important,
+            &mut self.warning,
+            &mut self.caution,
+        ]
+    }
+    pub fn set_mark_char(&mut self, c: char) {
+        for style in self.styles_mut() {
+            style.mark.set_char(c);
+        }
+    }
+    pub fn blend_with<C: Into<coolor::Color> + Copy>(&mut self, color: C, weight: f32) {
+        for style in self.styles_mut() {
+            style.blend_with(color, weight);
+        }
+    }
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        for style in self.styles_mut() {
+            style.limit_to_color_depth(depth);
+        }
+    }
+}
+
+/// Find the quotes starting with an admonition tag and change the
+/// kinds of their lines, the tag being replaced with a title
+pub(crate) fn find_admonitions(lines: &mut [FmtLine<'_>], skin: &MadSkin) {
+    let mut current = None;
+    for line in lines {
+        let FmtLine::Normal(fc) = line else {
+            current = None;
+            continue;
+        };
+        if fc.kind != CompositeKind::Quote(1) {
+            current = None;
+            continue;
+        }
+        let tag = match fc.compounds.as_slice() {
+            [compound] if !compound.code => AdmonitionKind::from_tag(compound.src),
+            _ => None,
+        };
+        if let Some(kind) = tag {
+            fc.kind = CompositeKind::AdmonitionTitle(kind);
+            fc.compounds = vec![Compound::raw_str(kind.label())];
+            fc.visible_length = skin.visible_composite_length(fc.kind, &fc.compounds);
+            current = Some(kind);
+        } else if let Some(kind) = current {
+            fc.kind = CompositeKind::Admonition(kind);
+        }
+    }
+}
+
+#[cfg(test)]
+mod admonition_tests {
+    use {
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_admonitions() {
+        let mut skin = MadSkin::no_style();
+        skin.admonitions.warning.mark.set_char('!');
+        let md = "> [!warning]\n> Don't do\n> that, as it's dangerous\n\n> [!NOTE]\n> ok\n\n> [!FOO]";
+        let text = FmtText::from(&skin, md, Some(20));
+        assert_eq!(
+            text.to_string(),
+            "! Warning\n! Don't do\n! that, as it's \n! dangerous\n\n▐ Note\n▐ ok\n\n▐ [!FOO]\n",
+        );
+        let FmtLine::Normal(fc) = &text.lines[3] else {
+            panic!("unexpected line");
+        };
+        assert_eq!(fc.kind, CompositeKind::Admonition(AdmonitionKind::Warning));
+    }
+}
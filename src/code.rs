@@ -1,8 +1,64 @@
 use {
-    crate::*,
-    minimad::Alignment,
+    crate::{
+        compound_range::SourcedCompound,
+        *,
+    },
+    minimad::{
+        Alignment,
+        Composite,
+    },
+    unicode_width::UnicodeWidthStr,
 };
 
+/// The top or bottom border of a framed code block
+/// (see [MadSkin::code_block_border_chars])
+#[derive(Debug, Clone)]
+pub struct FmtCodeFrame<'s> {
+    pub position: RelativePosition,
+    /// language of the fence, shown in the top border
+    pub lang: Option<&'s str>,
+    /// width of the line numbers, 0 when there's no gutter
+    pub gutter_width: usize,
+    /// width of the code, without decoration
+    pub width: usize,
+}
+
+/// A line of a framed code block
+#[derive(Debug, Clone)]
+pub struct FmtFramedCode<'s> {
+    pub composite: FmtComposite<'s>,
+    /// number of the line in the block, None for the continuation
+    /// of a wrapped line
+    pub number: Option<usize>,
+    /// width of the line numbers, 0 when there's no gutter
+    pub gutter_width: usize,
+}
+
+/// Return the width taken by the frame of a code block and its gutter
+pub const fn code_frame_decoration_width(gutter_width: usize) -> usize {
+    if gutter_width > 0 {
+        gutter_width + 7 // "│ 12 │ code │"
+    } else {
+        4 // "│ code │"
+    }
+}
+
+impl FmtCodeFrame<'_> {
+    pub const fn visible_length(&self) -> usize {
+        code_frame_decoration_width(self.gutter_width) + self.width
+    }
+}
+
+impl FmtFramedCode<'_> {
+    pub fn visible_length(&self) -> usize {
+        let width = self
+            .composite
+            .spacing
+            .map_or(self.composite.visible_length, |sp| sp.width);
+        code_frame_decoration_width(self.gutter_width) + width
+    }
+}
+
 /// a sequence of lines whose line-style is Code
 #[derive(Debug)]
 pub struct CodeBlock {
@@ -27,7 +83,7 @@ impl CodeBlock {
 const fn code_line_length(line: &FmtLine<'_>) -> Option<usize> {
     match line {
         FmtLine::Normal(fc) => match fc.kind {
-            CompositeKind::Code => Some(fc.visible_length),
+            CompositeKind::Code | CompositeKind::CodeFollowUp(_) => Some(fc.visible_length),
             _ => None,
         },
         _ => None,
@@ -75,3 +131,189 @@ pub fn justify_blocks(lines: &mut [FmtLine<'_>]) {
         b.justify(lines);
     }
 }
+
+/// Replace the code blocks with framed ones, wrapping the code lines
+/// which don't fit in the frame.
+///
+/// `fences` are the closed code fences, whose language is displayed in the
+/// top border of the block starting with their first line.
+pub fn frame_blocks<'s>(
+    lines: &mut Vec<FmtLine<'s>>,
+    fences: &[CodeFence<'s>],
+    width: Option<usize>,
+    skin: &MadSkin,
+) {
+    if skin.code_block_border_chars.is_none() {
+        return;
+    }
+    let (left_margin, right_margin) = skin.code_block.margins_in(width);
+    for block in find_blocks(lines).iter().rev() {
+        let gutter_width = if skin.code_block_line_numbers {
+            block.height.to_string().len()
+        } else {
+            0
+        };
+        let decoration_width = code_frame_decoration_width(gutter_width);
+        let available_width = width.map(|w| {
+            w.saturating_sub(left_margin + right_margin + decoration_width)
+        });
+        if available_width.map_or(false, |w| w < 3) {
+            continue; // we'll display the block without frame
+        }
+        let mut framed = Vec::new();
+        for (idx, line) in lines.drain(block.start..block.start + block.height).enumerate() {
+            let FmtLine::Normal(fc) = line else {
+                continue; // can't happen, it's a code block
+            };
+            let composites = match available_width {
+                Some(w) if fc.visible_length > w => wrap::hard_wrap_composite(&fc, w, skin)
+                    .unwrap_or_else(|_| vec![fc]),
+                _ => vec![fc],
+            };
+            for (i, composite) in composites.into_iter().enumerate() {
+                framed.push(FmtFramedCode {
+                    composite,
+                    number: if i == 0 { Some(idx + 1) } else { None },
+                    gutter_width,
+                });
+            }
+        }
+        let code_width = framed
+            .iter()
+            .map(|line| line.composite.visible_length)
+            .max()
+            .unwrap_or(0);
+        for line in &mut framed {
+            line.composite.spacing = Some(Spacing {
+                width: code_width,
+                align: Alignment::Left,
+            });
+        }
+        let lang = fences
+            .iter()
+            .find(|fence| fence.start == block.start)
+            .and_then(|fence| fence.lang);
+        let frame = |position| {
+            FmtLine::CodeFrame(FmtCodeFrame {
+                position,
+                lang,
+                gutter_width,
+                width: code_width,
+            })
+        };
+        let mut new_lines = vec![frame(RelativePosition::Top)];
+        new_lines.extend(framed.into_iter().map(FmtLine::FramedCode));
+        new_lines.push(frame(RelativePosition::Bottom));
+        lines.splice(block.start..block.start, new_lines);
+    }
+}
+
+/// Return the part of the top border of a code block
+/// after the gutter, with the language if it fits
+pub(crate) fn top_border_label(lang: Option<&str>, width: usize) -> Option<String> {
+    let lang = lang?;
+    if lang.width() + 3 > width {
+        return None;
+    }
+    Some(format!(" {} ", lang))
+}
+
+/// An opened code fence, whose language, if any, may be used
+/// to highlight the lines following it
+#[derive(Debug)]
+pub struct CodeFence<'s> {
+    pub lang: Option<&'s str>,
+    pub start: usize, // index of the first line after the fence
+}
+impl<'s> CodeFence<'s> {
+    pub fn new(composite: &Composite<'s>, start: usize) -> Self {
+        let lang = composite
+            .compounds
+            .first()
+            .and_then(|c| c.src.split_whitespace().next());
+        Self { lang, start }
+    }
+    /// highlight the code lines of the block, which are the lines
+    /// from the start of the fence to the end of the passed slice
+    pub fn highlight(&self, lines: &mut [FmtLine<'s>], skin: &MadSkin) {
+        let (Some(lang), Some(highlighter)) = (self.lang, skin.code_highlighter) else {
+            return;
+        };
+        let Some(code_lines) = lines.get_mut(self.start..) else {
+            return;
+        };
+        let srcs: Vec<&'s str> = code_lines
+            .iter()
+            .map(|line| match line {
+                FmtLine::Normal(fc) => fc.compounds.first().map_or("", |c| c.src),
+                _ => "",
+            })
+            .collect();
+        let Some(spans) = highlighter.highlight(lang, &srcs) else {
+            return;
+        };
+        for ((line, src), spans) in code_lines.iter_mut().zip(srcs).zip(spans) {
+            let FmtLine::Normal(fc) = line else {
+                continue;
+            };
+            if fc.kind != CompositeKind::Code || fc.compounds.len() != 1 {
+                continue;
+            }
+            let compound = SourcedCompound::new(0, fc.compounds[0].clone());
+            let mut compounds = Vec::new();
+            let mut idx = 0;
+            for span in spans {
+                if span.start < idx || src.get(span.start..span.end).is_none() {
+                    continue; // invalid span
+                }
+                if span.start > idx {
+                    compounds.push(compound.sub(idx, span.start));
+                }
+                compounds.push(compound.sub(span.start, span.end));
+                fc.code_tokens.push(CodeToken {
+                    range: CompoundRange::new(
+                        CompoundPos::new(0, span.start),
+                        CompoundPos::new(0, span.end),
+                    ),
+                    kind: span.kind,
+                });
+                idx = span.end;
+            }
+            if idx < src.len() || compounds.is_empty() {
+                compounds.push(compound.tail(idx));
+            }
+            fc.set_sourced_compounds(compounds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod code_tests {
+    use {
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_framed_code_blocks() {
+        let mut skin = MadSkin::no_style();
+        skin.code_block_border_chars = Some(ASCII_TABLE_BORDER_CHARS);
+        skin.code_block_line_numbers = true;
+        let md = "```rust\nlet a = 1;\nlet long_name = \"some string\";\n```";
+        let text = FmtText::from(&skin, md, Some(28));
+        assert_eq!(
+            text.to_string(),
+            concat!(
+                "+---+- rust -----------+\n",
+                "| 1 | let a = 1;       |\n",
+                "| 2 | let long_name =  |\n",
+                "|   | ↪ \"some string\"; |\n",
+                "+---+------------------+\n",
+            ),
+        );
+        // the search finds matches in framed lines
+        let mut search = TextSearch::new(SearchPattern::plain("long"));
+        search.update(&text);
+        assert_eq!(search.count(), 1);
+    }
+}
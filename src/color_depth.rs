@@ -0,0 +1,163 @@
+use {
+    crate::crossterm::style::Color,
+    std::sync::atomic::{
+        AtomicU8,
+        Ordering,
+    },
+};
+
+/// The colors a terminal can display
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// The 16 standard colors (black, red, ..., white, and their
+    /// bright versions)
+    Ansi16,
+    /// The 256 colors of the 8-bit palette
+    Ansi256,
+    /// All RGB colors
+    TrueColor,
+}
+
+static COLOR_DEPTH: AtomicU8 = AtomicU8::new(0);
+
+/// Set the color depth returned by [color_depth], overriding the
+/// detection, or restore the detection when `None` is passed
+pub fn set_color_depth(depth: Option<ColorDepth>) {
+    let value = match depth {
+        None => 0,
+        Some(ColorDepth::Ansi16) => 1,
+        Some(ColorDepth::Ansi256) => 2,
+        Some(ColorDepth::TrueColor) => 3,
+    };
+    COLOR_DEPTH.store(value, Ordering::Relaxed);
+}
+
+/// Return the color depth set with [set_color_depth] or, by default,
+/// the one detected from the `COLORTERM` and `TERM` environment variables
+pub fn color_depth() -> ColorDepth {
+    match COLOR_DEPTH.load(Ordering::Relaxed) {
+        1 => ColorDepth::Ansi16,
+        2 => ColorDepth::Ansi256,
+        3 => ColorDepth::TrueColor,
+        _ => ColorDepth::detect(),
+    }
+}
+
+impl ColorDepth {
+    /// Detect the color depth of the terminal from the `COLORTERM`
+    /// and `TERM` environment variables
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").ok();
+        let term = std::env::var("TERM").ok();
+        Self::from_env_values(colorterm.as_deref(), term.as_deref())
+    }
+
+    /// Determine the color depth from the values of the `COLORTERM`
+    /// and `TERM` environment variables
+    pub fn from_env_values(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if let Some(colorterm) = colorterm {
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                return Self::TrueColor;
+            }
+        }
+        let Some(term) = term.filter(|term| !term.is_empty()) else {
+            // without TERM, most likely a Windows console, which have
+            // supported RGB colors for years
+            return if cfg!(windows) { Self::TrueColor } else { Self::Ansi16 };
+        };
+        if term.ends_with("-direct") || term.contains("truecolor") || term.contains("24bit") {
+            Self::TrueColor
+        } else if term.contains("256") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Return the color, or the nearest one which can be displayed with
+    /// this color depth
+    pub fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::TrueColor, _) => color,
+            (Self::Ansi256, Color::Rgb { r, g, b }) => {
+                let coolor::AnsiColor { code } = coolor::Rgb { r, g, b }.to_ansi();
+                Color::AnsiValue(code)
+            }
+            (Self::Ansi256, _) => color,
+            (Self::Ansi16, Color::Rgb { .. } | Color::AnsiValue(_)) => {
+                let rgb = coolor::Color::from(color).rgb();
+                let coolor::AnsiColor { code } = rgb.nearest_ansi_in_range(0, 15);
+                ansi16_color(code)
+            }
+            (Self::Ansi16, _) => color,
+        }
+    }
+}
+
+/// Return the named color of a code of the 16 colors palette
+fn ansi16_color(code: u8) -> Color {
+    match code {
+        0 => Color::Black,
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        7 => Color::Grey,
+        8 => Color::DarkGrey,
+        9 => Color::Red,
+        10 => Color::Green,
+        11 => Color::Yellow,
+        12 => Color::Blue,
+        13 => Color::Magenta,
+        14 => Color::Cyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod color_depth_tests {
+    use {
+        crate::{
+            crossterm::style::Color,
+            *,
+        },
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_detection() {
+        let depth = ColorDepth::from_env_values;
+        assert_eq!(depth(Some("truecolor"), Some("xterm")), ColorDepth::TrueColor);
+        assert_eq!(depth(Some("24bit"), None), ColorDepth::TrueColor);
+        assert_eq!(depth(None, Some("xterm-direct")), ColorDepth::TrueColor);
+        assert_eq!(depth(None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(depth(Some(""), Some("screen-256color")), ColorDepth::Ansi256);
+        assert_eq!(depth(None, Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(depth(None, Some("linux")), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_skin_downgrade() {
+        let mut skin = MadSkin::default();
+        skin.bold.set_fg(rgb(255, 187, 0));
+        skin.headers[0].set_bg(rgb(0, 0, 120));
+        skin.bullet.set_fg(ansi(160));
+        skin.italic.set_fg(Color::Magenta);
+        let mut skin_256 = skin.clone();
+        skin_256.limit_to_color_depth(ColorDepth::Ansi256);
+        assert_eq!(skin_256.bold.get_fg(), Some(ansi(214)));
+        assert_eq!(skin_256.headers[0].compound_style.get_bg(), Some(ansi(18)));
+        assert_eq!(skin_256.bullet.get_fg(), Some(ansi(160)));
+        assert_eq!(skin_256.italic.get_fg(), Some(Color::Magenta));
+        skin.limit_to_color_depth(ColorDepth::Ansi16);
+        assert_eq!(skin.bold.get_fg(), Some(Color::Yellow));
+        assert_eq!(skin.headers[0].compound_style.get_bg(), Some(Color::DarkBlue));
+        assert_eq!(skin.bullet.get_fg(), Some(Color::Red));
+        assert_eq!(skin.italic.get_fg(), Some(Color::Magenta));
+        assert_eq!(skin.bullet.to_string(), "\u{1b}[91m•\u{1b}[39m");
+        let header = skin.headers[0].compound_style.apply_to("h").to_string();
+        assert!(header.starts_with("\u{1b}[44m"));
+    }
+}
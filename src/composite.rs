@@ -1,5 +1,13 @@
 use {
-    crate::*,
+    crate::{
+        compound_range::{
+            self,
+            CompoundOrigin,
+            SourcedCompound,
+        },
+        *,
+    },
+    lazy_regex::*,
     minimad::{
         Composite,
         Compound,
@@ -19,6 +27,72 @@ pub struct FmtComposite<'s> {
     pub visible_length: usize,
 
     pub spacing: Option<Spacing>,
+
+    /// highlighted tokens, for code lines
+    pub code_tokens: Vec<CodeToken>,
+
+    /// markdown links, whose text is in the compounds
+    pub links: Vec<FmtLink<'s>>,
+
+    /// parts with a specific style (search matches, selection), set only
+    /// on the copies of lines being displayed
+    pub marks: Vec<TextMark>,
+
+    /// spaces added by justification, as (end of a gap, number
+    /// of spaces written after it)
+    pub paddings: Vec<(CompoundPos, usize)>,
+
+    /// whether a hyphen is written after the compounds, the
+    /// line being broken inside a word
+    pub hyphenated: bool,
+
+    /// set when the composite continues the previous line, which was
+    /// broken by wrapping (and not by a line end of the source)
+    pub wrap_joint: Option<WrapJoint>,
+}
+
+/// How a line resulting from wrapping continues the previous one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapJoint {
+    /// the line was broken at spaces, which aren't written
+    Space,
+    /// nothing was removed at the break (the line was broken in a word,
+    /// between ideographs, or the spaces end the previous line)
+    Direct,
+}
+
+impl WrapJoint {
+    /// Return the text the joint stands for
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Space => " ",
+            Self::Direct => "",
+        }
+    }
+}
+
+/// If the composite is a nested quote (like `"> > text"` or `">> text"`),
+/// remove the inner quote marks and return the depth of the quote
+fn extract_nested_quote(kind: CompositeKind, compounds: &mut Vec<Compound<'_>>) -> Option<u8> {
+    let first = compounds.first_mut().filter(|c| !c.code)?;
+    let outer_depth = match kind {
+        CompositeKind::Quote(depth) => depth,
+        CompositeKind::Paragraph if first.src.starts_with(">>") => 0,
+        _ => return None,
+    };
+    let mut depth = outer_depth;
+    while let Some(src) = first.src.strip_prefix('>') {
+        first.src = src.strip_prefix(' ').unwrap_or(src);
+        depth = depth.saturating_add(1);
+    }
+    if first.src.is_empty() {
+        compounds.remove(0);
+    }
+    if depth > outer_depth {
+        Some(depth)
+    } else {
+        None
+    }
 }
 
 impl<'s> FmtComposite<'s> {
@@ -28,16 +102,38 @@ impl<'s> FmtComposite<'s> {
             compounds: Vec::new(),
             visible_length: 0,
             spacing: None,
+            code_tokens: Vec::new(),
+            links: Vec::new(),
+            marks: Vec::new(),
+            paddings: Vec::new(),
+            hyphenated: false,
+            wrap_joint: None,
         }
     }
     pub fn from(composite: Composite<'s>, skin: &MadSkin) -> Self {
-        let kind: CompositeKind = composite.style.into();
-        FmtComposite {
-            visible_length: skin.visible_composite_length(kind, &composite.compounds),
+        let mut composite = composite;
+        let mut kind: CompositeKind = composite.style.into();
+        let task_kind = task_list::extract_task_item(kind, &mut composite.compounds);
+        if let Some(task_kind) = task_kind {
+            kind = task_kind;
+        } else if let Some(depth) = extract_nested_quote(kind, &mut composite.compounds) {
+            kind = CompositeKind::Quote(depth);
+        } else if kind == CompositeKind::Paragraph {
+            let item_kind = list_numbering::extract_ordered_item(&mut composite.compounds);
+            if let Some(item_kind) = item_kind {
+                kind = item_kind;
+            }
+        }
+        let mut fc = FmtComposite {
             kind,
             compounds: composite.compounds,
-            spacing: None,
+            ..Self::new()
+        };
+        if kind != CompositeKind::Code {
+            link::extract_links(&mut fc, skin.link_rendering);
         }
+        fc.recompute_width(skin);
+        fc
     }
     pub fn from_compound(compound: Compound<'s>) -> Self {
         let mut fc = Self::new();
@@ -58,6 +154,33 @@ impl<'s> FmtComposite<'s> {
     pub fn add_compound(&mut self, compound: Compound<'s>) {
         self.visible_length += compound.src.width();
         self.compounds.push(compound);
+        self.visible_length += self.width_after(self.compounds.len() - 1);
+    }
+    /// Add the compounds of another composite, with its links, code
+    /// tokens and marks, and modify `visible_length` accordingly
+    pub(crate) fn append(&mut self, other: FmtComposite<'s>) {
+        let n = self.compounds.len();
+        self.links.extend(other.links.into_iter().map(|l| FmtLink {
+            text: l.text.shifted(n),
+            ..l
+        }));
+        self.code_tokens.extend(other.code_tokens.into_iter().map(|t| CodeToken {
+            range: t.range.shifted(n),
+            ..t
+        }));
+        self.marks.extend(other.marks.into_iter().map(|m| TextMark {
+            range: m.range.shifted(n),
+            ..m
+        }));
+        self.paddings.extend(
+            other
+                .paddings
+                .into_iter()
+                .map(|(end, added)| (CompoundPos::new(end.compound + n, end.byte), added)),
+        );
+        for compound in other.compounds {
+            self.add_compound(compound);
+        }
     }
     /// Ensure the cached visible_length is correct.
     ///
@@ -65,7 +188,162 @@ impl<'s> FmtComposite<'s> {
     /// this must be called if compounds are added,
     /// removed or modified without using the FmtComposite API
     pub fn recompute_width(&mut self, skin: &MadSkin) {
-        self.visible_length = skin.visible_composite_length(self.kind, &self.compounds);
+        self.visible_length = skin.visible_composite_length(self.kind, &self.compounds)
+            + (0..self.compounds.len()).map(|idx| self.width_after(idx)).sum::<usize>()
+            + usize::from(self.hyphenated);
+    }
+    /// Return the number of spaces written after the compound of given
+    /// index because of justification
+    pub fn padding_after(&self, idx: usize) -> usize {
+        let Some(compound) = self.compounds.get(idx) else {
+            return 0;
+        };
+        let end = CompoundPos::new(idx, compound.src.len());
+        self.paddings
+            .iter()
+            .filter(|&&(e, _)| e == end)
+            .map(|&(_, n)| n)
+            .sum()
+    }
+    /// Return the number of the footnote whose mark is written after
+    /// the compound of given index, the compound ending the text of a link
+    pub fn footnote_after(&self, idx: usize) -> Option<usize> {
+        let compound = self.compounds.get(idx)?;
+        self.links
+            .iter()
+            .find(|l| l.ends_with(idx, compound))
+            .and_then(|l| l.footnote)
+    }
+    /// Return the width of what's written after the compound of given
+    /// index besides its text: spaces added by justification and footnote mark
+    pub fn width_after(&self, idx: usize) -> usize {
+        self.padding_after(idx) + self.footnote_after(idx).map_or(0, link::footnote_mark_width)
+    }
+    /// Return the compounds, each one knowing its origin, for them to
+    /// be cut and given back with [Self::set_sourced_compounds]
+    pub(crate) fn sourced_compounds(&self) -> Vec<SourcedCompound<'s>> {
+        self.compounds
+            .iter()
+            .enumerate()
+            .map(|(idx, compound)| SourcedCompound::new(idx, compound.clone()))
+            .collect()
+    }
+    /// Replace the compounds with the given ones, built from them, the
+    /// links, code tokens, marks and paddings following their compounds.
+    ///
+    /// The visible length isn't updated.
+    pub(crate) fn set_sourced_compounds(&mut self, compounds: Vec<SourcedCompound<'s>>) {
+        let links = std::mem::take(&mut self.links);
+        let code_tokens = std::mem::take(&mut self.code_tokens);
+        let marks = std::mem::take(&mut self.marks);
+        let paddings = std::mem::take(&mut self.paddings);
+        self.set_compounds_following(&links, &code_tokens, &marks, &paddings, compounds);
+    }
+    /// Set the compounds of the composite, built from the ones of `src`,
+    /// with the links, code tokens, marks and paddings of `src` applying
+    /// to them.
+    ///
+    /// The visible length isn't updated.
+    pub(crate) fn set_compounds_from(
+        &mut self,
+        src: &FmtComposite<'s>,
+        compounds: Vec<SourcedCompound<'s>>,
+    ) {
+        self.set_compounds_following(
+            &src.links,
+            &src.code_tokens,
+            &src.marks,
+            &src.paddings,
+            compounds,
+        );
+    }
+    fn set_compounds_following(
+        &mut self,
+        links: &[FmtLink<'s>],
+        code_tokens: &[CodeToken],
+        marks: &[TextMark],
+        paddings: &[(CompoundPos, usize)],
+        compounds: Vec<SourcedCompound<'s>>,
+    ) {
+        let origins: Vec<Option<CompoundOrigin>> = compounds.iter().map(|c| c.origin).collect();
+        let follow = |range| compound_range::follow_range(range, &origins);
+        self.links = links
+            .iter()
+            .filter_map(|l| follow(l.text).map(|text| FmtLink { text, ..*l }))
+            .collect();
+        self.code_tokens = code_tokens
+            .iter()
+            .filter_map(|t| follow(t.range).map(|range| CodeToken { range, ..*t }))
+            .collect();
+        self.marks = marks
+            .iter()
+            .filter_map(|m| follow(m.range).map(|range| TextMark { range, ..*m }))
+            .collect();
+        // the spaces stay after the end of their gap, if it's kept
+        self.paddings = paddings
+            .iter()
+            .filter_map(|&(end, added)| {
+                let idx = compound_range::follow_compound_end(end, &origins)?;
+                Some((CompoundPos::new(idx, compounds[idx].compound.src.len()), added))
+            })
+            .collect();
+        self.compounds = compounds.into_iter().map(|c| c.compound).collect();
+    }
+    /// Widen the spaces between words so that the composite (whose
+    /// visible length includes bullets and quote marks) fills the given
+    /// width. Trailing spaces are removed.
+    ///
+    /// The gaps keep their source, so that they stay in the links and
+    /// marks around them: the added spaces are stored in `paddings`,
+    /// keyed by the end of their gap.
+    ///
+    /// This is done on wrapped lines, when the line style is justified.
+    pub fn justify(&mut self, width: usize) {
+        let is_gap = |c: &Compound<'_>| {
+            !c.code && !c.src.is_empty() && c.src.trim_matches(' ').is_empty()
+        };
+        let mut compounds = Vec::with_capacity(self.compounds.len());
+        for compound in self.sourced_compounds() {
+            if compound.compound.code {
+                compounds.push(compound);
+                continue;
+            }
+            let src = compound.compound.src;
+            let mut start = 0;
+            for m in regex!(" +").find_iter(src) {
+                if m.start() > start {
+                    compounds.push(compound.sub(start, m.start()));
+                }
+                compounds.push(compound.sub(m.start(), m.end()));
+                start = m.end();
+            }
+            if start < src.len() {
+                compounds.push(compound.tail(start));
+            }
+        }
+        while compounds.last().map_or(false, |c| is_gap(&c.compound)) {
+            if let Some(gap) = compounds.pop() {
+                self.visible_length -= gap.compound.src.len();
+            }
+        }
+        self.set_sourced_compounds(compounds);
+        let compounds = &self.compounds;
+        let first_word = compounds.iter().position(|c| !is_gap(c));
+        let gaps: Vec<usize> = match first_word {
+            Some(first_word) => (first_word..compounds.len())
+                .filter(|&i| is_gap(&compounds[i]))
+                .collect(),
+            None => Vec::new(),
+        };
+        if !gaps.is_empty() && self.visible_length < width {
+            let extra = width - self.visible_length;
+            for (idx, &i) in gaps.iter().enumerate() {
+                let added = extra / gaps.len() + usize::from(idx < extra % gaps.len());
+                let end = CompoundPos::new(i, compounds[i].src.len());
+                self.paddings.push((end, added));
+                self.visible_length += added;
+            }
+        }
     }
     /// try to ensure the composite's width doesn't exceed the given
     /// width.
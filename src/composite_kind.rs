@@ -10,8 +10,27 @@ pub enum CompositeKind {
     Header(u8),
     ListItem(u8),
     ListItemFollowUp(u8),
+    /// an item of an ordered list, with its depth and number
+    OrderedListItem(u8, usize),
+    OrderedListItemFollowUp(u8, usize),
+    /// an item of a task list, with its depth and whether it's checked
+    TaskListItem(u8, bool),
     Code,
-    Quote,
+    /// the continuation of a wrapped code line, with the
+    /// width of the indentation of the code line
+    CodeFollowUp(usize),
+    /// a quote, with its depth (1 for a simple quote)
+    Quote(u8),
+    /// the title of a GitHub style admonition, which replaces its tag
+    AdmonitionTitle(AdmonitionKind),
+    /// a line of the content of an admonition
+    Admonition(AdmonitionKind),
+    /// a line of the list of the URLs of links rendered as footnotes,
+    /// with the number of the footnote, written before the URL
+    Footnote(usize),
+    /// a cell of a stacked table without header, with the number of
+    /// its column, written before the cell as label
+    StackedCell(usize),
 }
 
 impl From<CompositeStyle> for CompositeKind {
@@ -21,7 +40,7 @@ impl From<CompositeStyle> for CompositeKind {
             CompositeStyle::Header(level) => Self::Header(level),
             CompositeStyle::ListItem(level) => Self::ListItem(level),
             CompositeStyle::Code => Self::Code,
-            CompositeStyle::Quote => Self::Quote,
+            CompositeStyle::Quote => Self::Quote(1),
         }
     }
 }
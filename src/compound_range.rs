@@ -0,0 +1,206 @@
+use minimad::Compound;
+
+/// A position in the compounds of a formatted composite: the
+/// index of a compound and a byte index in its source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CompoundPos {
+    pub compound: usize,
+    pub byte: usize,
+}
+
+impl CompoundPos {
+    pub const fn new(compound: usize, byte: usize) -> Self {
+        Self { compound, byte }
+    }
+}
+
+/// A part of a formatted composite, like the text of a link or a
+/// search match, from the start position to the end one (excluded).
+///
+/// The range follows the compounds when they're cut (for example
+/// when the composite is wrapped). An end at the start of a compound
+/// (`byte` being 0) tells the range goes on after the previous
+/// compound, out of the composite or in a removed part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompoundRange {
+    pub start: CompoundPos,
+    pub end: CompoundPos,
+}
+
+impl CompoundRange {
+    pub const fn new(start: CompoundPos, end: CompoundPos) -> Self {
+        Self { start, end }
+    }
+    /// Return the range covering the compound of the given index
+    pub fn of_compound(idx: usize, compound: &Compound<'_>) -> Self {
+        Self {
+            start: CompoundPos::new(idx, 0),
+            end: CompoundPos::new(idx, compound.src.len()),
+        }
+    }
+    /// Tell whether the compound of given index is in the range
+    pub fn contains(&self, idx: usize, compound: &Compound<'_>) -> bool {
+        self.start <= CompoundPos::new(idx, 0)
+            && CompoundPos::new(idx, compound.src.len()) <= self.end
+    }
+    /// Tell whether the range ends with the compound of given index
+    pub fn ends_with(&self, idx: usize, compound: &Compound<'_>) -> bool {
+        !compound.src.is_empty()
+            && self.contains(idx, compound)
+            && self.end == CompoundPos::new(idx, compound.src.len())
+    }
+    /// Return the range moved by `n` compounds, to follow compounds
+    /// inserted before its ones
+    pub const fn shifted(self, n: usize) -> Self {
+        Self {
+            start: CompoundPos::new(self.start.compound + n, self.start.byte),
+            end: CompoundPos::new(self.end.compound + n, self.end.byte),
+        }
+    }
+}
+
+/// Where a compound comes from: the index of the compound of the
+/// composite it's a part of, and the byte range of the part
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CompoundOrigin {
+    pub idx: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A compound built from a part of a compound of a composite,
+/// knowing where it comes from, so that the ranges and paddings
+/// of the composite can follow it
+#[derive(Debug, Clone)]
+pub(crate) struct SourcedCompound<'s> {
+    pub compound: Compound<'s>,
+    /// None for a compound which isn't in the composite (like an ellipsis)
+    pub origin: Option<CompoundOrigin>,
+}
+
+impl<'s> SourcedCompound<'s> {
+    pub fn new(idx: usize, compound: Compound<'s>) -> Self {
+        let origin = Some(CompoundOrigin {
+            idx,
+            start: 0,
+            end: compound.src.len(),
+        });
+        Self { compound, origin }
+    }
+    /// Build a compound which isn't a part of the composite
+    pub fn inserted(compound: Compound<'s>) -> Self {
+        Self {
+            compound,
+            origin: None,
+        }
+    }
+    /// Return the part between the given byte indices
+    pub fn sub(&self, start: usize, end: usize) -> Self {
+        Self {
+            compound: self.compound.sub(start, end),
+            origin: self.origin.map(|o| CompoundOrigin {
+                idx: o.idx,
+                start: o.start + start,
+                end: o.start + end,
+            }),
+        }
+    }
+    /// Return the part starting at the given byte index
+    pub fn tail(&self, start: usize) -> Self {
+        self.sub(start, self.compound.src.len())
+    }
+}
+
+/// Return the position, in the compounds of the given origins,
+/// of the start of a range
+fn follow_start(pos: CompoundPos, origins: &[Option<CompoundOrigin>]) -> CompoundPos {
+    for (idx, o) in origins.iter().enumerate() {
+        let Some(o) = o else {
+            continue;
+        };
+        if pos < CompoundPos::new(o.idx, o.end) {
+            if pos > CompoundPos::new(o.idx, o.start) {
+                return CompoundPos::new(idx, pos.byte - o.start);
+            }
+            return CompoundPos::new(idx, 0);
+        }
+    }
+    CompoundPos::new(origins.len(), 0)
+}
+
+/// Return the position, in the compounds of the given origins,
+/// of the end of a range
+fn follow_end(pos: CompoundPos, origins: &[Option<CompoundOrigin>]) -> CompoundPos {
+    for (idx, o) in origins.iter().enumerate().rev() {
+        let Some(o) = o else {
+            continue;
+        };
+        if CompoundPos::new(o.idx, o.start) < pos {
+            if pos <= CompoundPos::new(o.idx, o.end) {
+                return CompoundPos::new(idx, pos.byte - o.start);
+            }
+            return CompoundPos::new(idx + 1, 0);
+        }
+    }
+    CompoundPos::new(0, 0)
+}
+
+/// Return the range, in the compounds of the given origins, matching
+/// the given one, None if it's not in any of them
+pub(crate) fn follow_range(
+    range: CompoundRange,
+    origins: &[Option<CompoundOrigin>],
+) -> Option<CompoundRange> {
+    let start = follow_start(range.start, origins);
+    let end = follow_end(range.end, origins);
+    if start < end {
+        Some(CompoundRange { start, end })
+    } else {
+        None
+    }
+}
+
+/// Return the index, in the compounds of the given origins, of the
+/// compound ending at the given position, if it's still there
+pub(crate) fn follow_compound_end(
+    end: CompoundPos,
+    origins: &[Option<CompoundOrigin>],
+) -> Option<usize> {
+    origins
+        .iter()
+        .position(|o| o.map_or(false, |o| o.idx == end.compound && o.end == end.byte))
+}
+
+#[cfg(test)]
+mod compound_range_tests {
+    use {
+        super::*,
+        pretty_assertions::assert_eq,
+    };
+
+    fn origin(idx: usize, start: usize, end: usize) -> Option<CompoundOrigin> {
+        Some(CompoundOrigin { idx, start, end })
+    }
+
+    #[test]
+    fn test_follow_range() {
+        // compounds "abc", "de", cut into "ab" and "c" on one line, "de"
+        // on the next one, with an ellipsis
+        let line_1 = [origin(0, 0, 2), origin(0, 2, 3), None];
+        let line_2 = [origin(1, 0, 2)];
+        let range = CompoundRange::new(CompoundPos::new(0, 2), CompoundPos::new(1, 2));
+        assert_eq!(
+            follow_range(range, &line_1),
+            // the range goes on after the "c" compound
+            Some(CompoundRange::new(CompoundPos::new(1, 0), CompoundPos::new(2, 0))),
+        );
+        assert_eq!(
+            follow_range(range, &line_2),
+            Some(CompoundRange::new(CompoundPos::new(0, 0), CompoundPos::new(0, 2))),
+        );
+        let range = CompoundRange::new(CompoundPos::new(0, 0), CompoundPos::new(0, 2));
+        assert_eq!(follow_range(range, &line_2), None);
+        assert_eq!(follow_compound_end(CompoundPos::new(0, 3), &line_1), Some(1));
+        assert_eq!(follow_compound_end(CompoundPos::new(0, 3), &line_2), None);
+    }
+}
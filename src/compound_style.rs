@@ -1,15 +1,13 @@
 use {
     crate::{
+        color_depth::ColorDepth,
         crossterm::{
             style::{
                 Attribute,
                 Attributes,
                 Color,
                 ContentStyle,
-                PrintStyledContent,
-                SetBackgroundColor,
-                SetForegroundColor,
-                StyledContent,
+                Print,
             },
             terminal::{
                 Clear,
@@ -18,6 +16,10 @@ use {
             QueueableCommand,
         },
         errors::Result,
+        styled::{
+            self,
+            Styled,
+        },
         styled_char::StyledChar,
     },
     std::fmt::{
@@ -51,12 +53,12 @@ impl From<ContentStyle> for CompoundStyle {
 }
 
 impl CompoundStyle {
-    /// Apply an `StyledContent` to the passed displayable object.
-    pub fn apply_to<D>(&self, val: D) -> StyledContent<D>
+    /// Apply the style to the passed displayable object.
+    pub fn apply_to<D>(&self, val: D) -> Styled<D>
     where
         D: Clone + Display,
     {
-        self.object_style.apply(val)
+        Styled::new(self.object_style, val)
     }
 
     /// Get an new instance of `CompoundStyle`
@@ -80,7 +82,7 @@ impl CompoundStyle {
     ///
     /// The `dest` color can be for example a [crossterm] color or a [coolor] one.
     pub fn blend_with<C: Into<coolor::Color>>(&mut self, dest: C, weight: f32) {
-        debug_assert!(weight >= 0.0 && weight <= 1.0);
+        debug_assert!((0.0..=1.0).contains(&weight));
         let dest: coolor::Color = dest.into();
         if let Some(fg) = self.object_style.foreground_color.as_mut() {
             let src: coolor::Color = (*fg).into();
@@ -92,6 +94,21 @@ impl CompoundStyle {
         }
     }
 
+    /// Replace the colors (foreground, background, underline) with the nearest
+    /// ones which can be displayed with the given color depth
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        let style = &mut self.object_style;
+        for color in [
+            &mut style.foreground_color,
+            &mut style.background_color,
+            &mut style.underline_color,
+        ] {
+            if let Some(color) = color.as_mut() {
+                *color = depth.convert(*color);
+            }
+        }
+    }
+
     /// Get an new instance of `CompoundStyle`
     pub fn with_fgbg(fg: Color, bg: Color) -> Self {
         Self::new(Some(fg), Some(bg), Attributes::default())
@@ -206,7 +223,7 @@ impl CompoundStyle {
         D: Clone + Display,
         W: std::io::Write,
     {
-        w.queue(PrintStyledContent(self.apply_to(val)))?;
+        w.queue(Print(self.apply_to(val)))?;
         Ok(())
     }
 
@@ -224,7 +241,9 @@ impl CompoundStyle {
         W: std::io::Write,
     {
         if let Some(fg) = self.object_style.foreground_color {
-            w.queue(SetForegroundColor(fg))?;
+            let mut sgr = String::new();
+            let _ = styled::write_fg(&mut sgr, fg);
+            w.queue(Print(sgr))?;
         }
         Ok(())
     }
@@ -234,7 +253,9 @@ impl CompoundStyle {
         W: std::io::Write,
     {
         if let Some(bg) = self.object_style.background_color {
-            w.queue(SetBackgroundColor(bg))?;
+            let mut sgr = String::new();
+            let _ = styled::write_bg(&mut sgr, bg);
+            w.queue(Print(sgr))?;
         }
         Ok(())
     }
@@ -21,6 +21,22 @@ pub struct EscapeSequence {
     pub keys: Vec<KeyEvent>,
 }
 
+impl EscapeSequence {
+    /// Return the chars of the sequence, without the keys with
+    /// an ALT or CONTROL modifier (like the ones starting and
+    /// ending the sequence)
+    pub fn text(&self) -> String {
+        self.keys
+            .iter()
+            .filter(|key| !key.modifiers.intersects(KeyModifiers::ALT | KeyModifiers::CONTROL))
+            .filter_map(|key| match key.code {
+                KeyCode::Char(c) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
 impl fmt::Display for EscapeSequence {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for key in &self.keys {
@@ -0,0 +1,160 @@
+use {
+    super::TimedEvent,
+    crate::crossterm::event::Event,
+    crokey::KeyCombination,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// An event of a recording, with its time relative to
+/// the first event of the recording
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// milliseconds since the first event of the recording
+    pub millis: u64,
+    pub event: Event,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub double_click: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_combination: Option<KeyCombination>,
+}
+
+/// The events emitted by an [EventSource](crate::EventSource) while it
+/// was recording (see [EventSourceOptions::record](crate::EventSourceOptions::record)
+/// and [EventSource::start_recording](crate::EventSource::start_recording)).
+///
+/// Escape sequences aren't recorded.
+///
+/// A recording can be serialized (for example in JSON) to be
+/// saved, then replayed with
+/// [with_recording](crate::EventSource::with_recording).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventRecording {
+    pub events: Vec<RecordedEvent>,
+}
+
+/// How the delays between the events of a recording are honored
+/// when replaying it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayTiming {
+    /// Wait as long as in the recording
+    Original,
+    /// Wait as in the recording, but never more than the given duration
+    /// between two events
+    Capped(Duration),
+    /// Send the events without waiting
+    Immediate,
+}
+
+impl ReplayTiming {
+    /// Return how long to wait for an event coming `delay` after the previous one
+    pub fn wait(self, delay: Duration) -> Duration {
+        match self {
+            Self::Original => delay,
+            Self::Capped(max) => delay.min(max),
+            Self::Immediate => Duration::ZERO,
+        }
+    }
+}
+
+impl EventRecording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+    /// Return the duration between the first and the last events
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.events.last().map_or(0, |e| e.millis))
+    }
+}
+
+/// What's recording the events in the event source
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    start: Option<Instant>,
+    recording: EventRecording,
+}
+
+impl Recorder {
+    pub fn record(&mut self, timed_event: &TimedEvent) {
+        let start = *self.start.get_or_insert(timed_event.time);
+        let delay = timed_event.time.saturating_duration_since(start);
+        self.recording.events.push(RecordedEvent {
+            millis: delay.as_millis() as u64,
+            event: timed_event.event.clone(),
+            double_click: timed_event.double_click,
+            key_combination: timed_event.key_combination,
+        });
+    }
+    pub fn into_recording(self) -> EventRecording {
+        self.recording
+    }
+}
+
+#[cfg(test)]
+mod event_recording_tests {
+    use {
+        crate::*,
+        crokey::key,
+        std::time::Duration,
+    };
+
+    fn play(event_source: &EventSource) -> Vec<TimedEvent> {
+        let rx = event_source.receiver();
+        let mut events = Vec::new();
+        while let Ok(timed_event) = rx.recv() {
+            events.push(timed_event);
+            event_source.unblock(false);
+        }
+        events
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let script = EventScript::new()
+            .key(key!(ctrl-a))
+            .wait_millis(300)
+            .double_click(4, 2)
+            .wait_millis(2000)
+            .resize(30, 10);
+        let options = EventSourceOptions {
+            record: true,
+            ..Default::default()
+        };
+        let event_source = EventSource::with_script(script, options);
+        let original = play(&event_source);
+        let recording = event_source.stop_recording().unwrap();
+        assert_eq!(event_source.stop_recording(), None);
+        assert_eq!(recording.len(), 6);
+        assert_eq!(recording.duration(), Duration::from_millis(2350));
+
+        // the recording survives serialization
+        let json = serde_json::to_string(&recording).unwrap();
+        let recording: EventRecording = serde_json::from_str(&json).unwrap();
+
+        let replayed = play(&EventSource::with_recording(
+            recording,
+            ReplayTiming::Capped(Duration::from_millis(1)),
+            EventSourceOptions::default(),
+        ));
+        assert_eq!(replayed.len(), original.len());
+        for (a, b) in original.iter().zip(&replayed) {
+            assert_eq!(a.event, b.event);
+            assert_eq!(a.double_click, b.double_click);
+            assert_eq!(a.key_combination, b.key_combination);
+        }
+        // the times of the replayed events are the recorded ones
+        assert_eq!(replayed[5].time - replayed[0].time, Duration::from_millis(2350));
+    }
+}
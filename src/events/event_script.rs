@@ -0,0 +1,158 @@
+use {
+    crate::crossterm::event::{
+        Event,
+        KeyCode,
+        KeyEvent,
+        KeyModifiers,
+        MouseButton,
+        MouseEvent,
+        MouseEventKind,
+    },
+    crokey::KeyCombination,
+    std::time::Duration,
+};
+
+/// A list of events, with their times, to feed an [EventSource](crate::EventSource)
+/// built with [with_script](crate::EventSource::with_script), for example to
+/// test an application without a terminal.
+///
+/// Times are virtual: the script is played without waiting, but the events
+/// are dated according to the delays given with [wait](Self::wait), so that
+/// double clicks are detected as with real events.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventScript {
+    /// the events, with their delay since the start of the script
+    pub(crate) events: Vec<(Duration, Event)>,
+    now: Duration,
+}
+
+impl EventScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Advance the virtual clock
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.now += duration;
+        self
+    }
+    /// Advance the virtual clock by some milliseconds
+    pub fn wait_millis(self, millis: u64) -> Self {
+        self.wait(Duration::from_millis(millis))
+    }
+    /// Add any crossterm event
+    pub fn event(mut self, event: Event) -> Self {
+        self.events.push((self.now, event));
+        self
+    }
+    /// Add the press of a key combination, for example `key!(ctrl-q)`
+    /// or `KeyCode::Enter`.
+    ///
+    /// As the source doesn't combine keys, a combination of several
+    /// non modifier keys is sent as successive key presses.
+    pub fn key<K: Into<KeyCombination>>(mut self, key: K) -> Self {
+        let key = key.into();
+        for code in key.codes.to_vec() {
+            self = self.event(Event::Key(KeyEvent::new(code, key.modifiers)));
+        }
+        self
+    }
+    /// Add the presses of the keys typing the given text
+    pub fn text(mut self, text: &str) -> Self {
+        for c in text.chars() {
+            self = self.key(KeyCode::Char(c));
+        }
+        self
+    }
+    /// Add a mouse event with the given kind at the given position
+    pub fn mouse(self, kind: MouseEventKind, x: u16, y: u16) -> Self {
+        self.event(Event::Mouse(MouseEvent {
+            kind,
+            column: x,
+            row: y,
+            modifiers: KeyModifiers::NONE,
+        }))
+    }
+    /// Add a left click (mouse down then up) at the given position
+    pub fn click(self, x: u16, y: u16) -> Self {
+        self.mouse(MouseEventKind::Down(MouseButton::Left), x, y)
+            .mouse(MouseEventKind::Up(MouseButton::Left), x, y)
+    }
+    /// Add two quick left clicks at the given position
+    pub fn double_click(self, x: u16, y: u16) -> Self {
+        self.click(x, y).wait_millis(50).click(x, y)
+    }
+    /// Add a terminal resize
+    pub fn resize(self, width: u16, height: u16) -> Self {
+        self.event(Event::Resize(width, height))
+    }
+    /// Return the number of events in the script
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod event_script_tests {
+    use {
+        crate::{
+            crossterm::event::{
+                Event,
+                KeyCode,
+            },
+            *,
+        },
+        crokey::{
+            key,
+            KeyCombination,
+        },
+    };
+
+    fn play(script: EventScript) -> Vec<TimedEvent> {
+        let event_source = EventSource::with_script(script, EventSourceOptions::default());
+        let rx = event_source.receiver();
+        let mut events = Vec::new();
+        while let Ok(timed_event) = rx.recv() {
+            events.push(timed_event);
+            event_source.unblock(false);
+        }
+        events
+    }
+
+    #[test]
+    fn test_keys_and_resize() {
+        let events = play(
+            EventScript::new()
+                .text("a")
+                .key(key!(ctrl-q))
+                .resize(80, 20)
+                .key(KeyCode::Enter),
+        );
+        let keys: Vec<Option<KeyCombination>> =
+            events.iter().map(|e| e.key_combination).collect();
+        assert_eq!(
+            keys,
+            vec![Some(key!(a)), Some(key!(ctrl-q)), None, Some(key!(enter))],
+        );
+        assert_eq!(events[2].event, Event::Resize(80, 20));
+    }
+
+    #[test]
+    fn test_double_click() {
+        let events = play(
+            EventScript::new()
+                .click(2, 3)
+                .wait_millis(1000)
+                .click(2, 3)
+                .double_click(5, 1),
+        );
+        let flags: Vec<bool> = events.iter().map(|e| e.double_click).collect();
+        assert_eq!(
+            flags,
+            vec![false, false, false, false, false, false, true, true],
+        );
+        assert_eq!(events[2].time - events[0].time, std::time::Duration::from_secs(1));
+    }
+}
@@ -1,6 +1,10 @@
 use {
     super::{
+        event_recording::Recorder,
         EscapeSequence,
+        EventRecording,
+        EventScript,
+        ReplayTiming,
         TimedEvent,
     },
     crate::{
@@ -33,6 +37,7 @@ use {
                 Ordering,
             },
             Arc,
+            Mutex,
         },
         thread,
         time::{
@@ -51,6 +56,11 @@ struct TimedClick {
     y: u16,
 }
 
+/// Options of an [EventSource].
+///
+/// Start from `EventSourceOptions::default()` and change the fields you need,
+/// as new ones may be added.
+#[non_exhaustive]
 pub struct EventSourceOptions {
     /// Whether to try combine key events into key combinations.
     /// This changes the behavior of the terminal, if it's compatible, then restores
@@ -73,12 +83,15 @@ pub struct EventSourceOptions {
     pub discard_mouse_move: bool,
     /// whether to filter out mouse drag (default false)
     pub discard_mouse_drag: bool,
+    /// whether to record the emitted events from the start (default false),
+    /// see [EventSource::stop_recording]
+    pub record: bool,
 }
 
 /// a thread backed event listener emmiting events on a channel.
 ///
 /// The event source enables the terminal's raw mode and restores
-///  it on drop
+///  it on drop (except when it's built from a script or a recording)
 ///
 /// Additionnally to emmitting events, this source updates a
 ///  sharable event count, protected by an Arc. This makes
@@ -89,10 +102,12 @@ pub struct EventSourceOptions {
 /// built TUI with no CPU consumption while idle.
 pub struct EventSource {
     is_combining_keys: bool,
+    raw_mode: bool, // whether raw mode was enabled by the source
     rx_events: Receiver<TimedEvent>,
     rx_seqs: Receiver<EscapeSequence>,
     tx_quit: Sender<bool>,
     event_count: Arc<AtomicUsize>,
+    recorder: Arc<Mutex<Option<Recorder>>>,
 }
 
 impl Default for EventSourceOptions {
@@ -103,6 +118,7 @@ impl Default for EventSourceOptions {
             discard_raw_key_events: true,
             discard_mouse_move: true,
             discard_mouse_drag: false,
+            record: false,
         }
     }
 }
@@ -114,6 +130,127 @@ fn is_seq_end(key: KeyEvent) -> bool {
     key.code == KeyCode::Char('\\') && key.modifiers == KeyModifiers::ALT
 }
 
+/// The part of the event source living in the thread, which decorates
+/// and filters the crossterm events before sending them
+struct EventProcessor {
+    options: EventSourceOptions,
+    combiner: Combiner,
+    last_up: Option<TimedClick>,
+    current_escape_sequence: Option<EscapeSequence>,
+    tx_events: Sender<TimedEvent>,
+    tx_seqs: Sender<EscapeSequence>,
+    rx_quit: Receiver<bool>,
+    event_count: Arc<AtomicUsize>,
+    recorder: Arc<Mutex<Option<Recorder>>>,
+}
+
+impl EventProcessor {
+    /// return true when we must close the source
+    fn send_and_wait(&self, event: TimedEvent) -> bool {
+        self.event_count.fetch_add(1, Ordering::SeqCst);
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            recorder.record(&event);
+        }
+        if self.tx_events.send(event).is_err() {
+            true // broken channel
+        } else {
+            !matches!(self.rx_quit.recv(), Ok(false))
+        }
+    }
+    /// handle an event read at the given time.
+    /// Return true when we must close the source
+    fn process(&mut self, ct_event: Event, time: Instant) -> bool {
+        let options = &self.options;
+        let in_seq = self.current_escape_sequence.is_some();
+        if in_seq {
+            if let crossterm::event::Event::Key(key) = ct_event {
+                if is_seq_end(key) {
+                    // it's a proper sequence ending, we send it as such
+                    let mut seq = self.current_escape_sequence.take().unwrap();
+                    seq.keys.push(key);
+                    if self.tx_seqs.try_send(seq).is_err() {
+                        // there's probably just nobody listening on
+                        // this zero size bounded channel
+                    }
+                    return false;
+                } else if !key
+                    .modifiers
+                    .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)
+                {
+                    // adding to the current escape sequence
+                    self.current_escape_sequence.as_mut().unwrap().keys.push(key);
+                    return false;
+                }
+            }
+            // it's neither part of a proper sequence, nor the end
+            // we send all previous events independently before sending this one
+            let seq = self.current_escape_sequence.take().unwrap();
+            for key in seq.keys {
+                let mut timed_event = TimedEvent::new(Event::Key(key));
+                timed_event.time = time;
+                timed_event.key_combination = self.combiner.transform(key);
+                if options.discard_raw_key_events && timed_event.key_combination.is_none() {
+                    continue;
+                }
+                if self.send_and_wait(timed_event) {
+                    return true;
+                }
+            }
+            // the current event will be sent normally
+        } else if let crossterm::event::Event::Key(key) = ct_event {
+            if is_seq_start(key) {
+                // starting a new sequence
+                self.current_escape_sequence = Some(EscapeSequence { keys: vec![key] });
+                return false;
+            }
+        }
+        let options = &self.options;
+        if let Event::Mouse(mouse_event) = ct_event {
+            if options.discard_mouse_move && mouse_event.kind == MouseEventKind::Moved {
+                return false;
+            }
+            if options.discard_mouse_drag && matches!(mouse_event.kind, MouseEventKind::Drag(_)) {
+                return false;
+            }
+        }
+        let mut timed_event = TimedEvent::new(ct_event);
+        timed_event.time = time;
+        if let Event::Key(key) = &timed_event.event {
+            timed_event.key_combination = self.combiner.transform(*key);
+            if options.discard_raw_key_events && timed_event.key_combination.is_none() {
+                return false;
+            }
+        }
+        if let Event::Mouse(MouseEvent {
+            kind, column, row, ..
+        }) = timed_event.event
+        {
+            if matches!(
+                kind,
+                MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Up(MouseButton::Left)
+            ) {
+                if let Some(TimedClick { time, x, y }) = self.last_up {
+                    if column == x
+                        && row == y
+                        && timed_event.time - time < DOUBLE_CLICK_MAX_DURATION
+                    {
+                        timed_event.double_click = true;
+                    }
+                }
+                if kind == MouseEventKind::Up(MouseButton::Left) {
+                    self.last_up = Some(TimedClick {
+                        time: timed_event.time,
+                        x: column,
+                        y: row,
+                    });
+                }
+            }
+        }
+        // we send the event to the receiver in the main event loop
+        self.send_and_wait(timed_event)
+    }
+}
+
 impl EventSource {
     /// create a new source with default options
     ///
@@ -146,139 +283,146 @@ impl EventSource {
         } else {
             false
         };
-        combiner.set_mandate_modifier_for_multiple_keys(options.mandate_modifier_for_multiple_keys);
-        let (tx_events, rx_events) = unbounded();
-        let (tx_seqs, rx_seqs) = bounded(ESCAPE_SEQUENCE_CHANNEL_SIZE);
-        let (tx_quit, rx_quit) = unbounded();
-        let event_count = Arc::new(AtomicUsize::new(0));
-        let internal_event_count = Arc::clone(&event_count);
-        thread::spawn(move || {
-            let mut last_up: Option<TimedClick> = None;
-            let mut current_escape_sequence: Option<EscapeSequence> = None;
-            // return true when we must close the source
-            let send_and_wait = |event| {
-                internal_event_count.fetch_add(1, Ordering::SeqCst);
-                if tx_events.send(event).is_err() {
-                    true // broken channel
-                } else {
-                    match rx_quit.recv() {
-                        Ok(false) => false,
-                        _ => true,
-                    }
+        let (source, mut processor) = Self::build(options, combiner, is_combining_keys, true);
+        thread::spawn(move || loop {
+            let ct_event = match crossterm::event::read() {
+                Ok(e) => e,
+                _ => {
+                    continue;
                 }
             };
-            loop {
-                let ct_event = match crossterm::event::read() {
-                    Ok(e) => e,
-                    _ => {
-                        continue;
-                    }
-                };
-                let in_seq = current_escape_sequence.is_some();
-                if in_seq {
-                    if let crossterm::event::Event::Key(key) = ct_event {
-                        if is_seq_end(key) {
-                            // it's a proper sequence ending, we send it as such
-                            let mut seq = current_escape_sequence.take().unwrap();
-                            seq.keys.push(key);
-                            if tx_seqs.try_send(seq).is_err() {
-                                // there's probably just nobody listening on
-                                // this zero size bounded channel
-                            }
-                            continue;
-                        } else if !key
-                            .modifiers
-                            .intersects(KeyModifiers::ALT | KeyModifiers::CONTROL)
-                        {
-                            // adding to the current escape sequence
-                            current_escape_sequence.as_mut().unwrap().keys.push(key);
-                            continue;
-                        }
-                    }
-                    // it's neither part of a proper sequence, nor the end
-                    // we send all previous events independently before sending this one
-                    let seq = current_escape_sequence.take().unwrap();
-                    for key in seq.keys {
-                        let mut timed_event = TimedEvent::new(Event::Key(key));
-                        timed_event.key_combination = combiner.transform(key);
-                        if options.discard_raw_key_events && timed_event.key_combination.is_none() {
-                            continue;
-                        }
-                        if send_and_wait(timed_event) {
-                            return;
-                        }
-                    }
-                    // the current event will be sent normally
-                } else if let crossterm::event::Event::Key(key) = ct_event {
-                    if is_seq_start(key) {
-                        // starting a new sequence
-                        current_escape_sequence = Some(EscapeSequence { keys: vec![key] });
-                        continue;
-                    }
-                }
-                if let Event::Mouse(mouse_event) = ct_event {
-                    if options.discard_mouse_move && mouse_event.kind == MouseEventKind::Moved {
-                        continue;
-                    }
-                    if options.discard_mouse_drag
-                        && matches!(mouse_event.kind, MouseEventKind::Drag(_))
-                    {
-                        continue;
-                    }
-                }
-                let mut timed_event = TimedEvent::new(ct_event);
-                if let Event::Key(key) = &timed_event.event {
-                    timed_event.key_combination = combiner.transform(*key);
-                    if options.discard_raw_key_events && timed_event.key_combination.is_none() {
-                        continue;
-                    }
+            if processor.process(ct_event, Instant::now()) {
+                return;
+            }
+        });
+        Ok(source)
+    }
+    /// create a source emitting the events of a script, without
+    /// reading the terminal nor changing its mode.
+    ///
+    /// The events are decorated (key combinations, double clicks) and
+    /// filtered according to the options, like the ones of a normal source,
+    /// but key combining isn't available.
+    ///
+    /// The events are sent without delay, with the times defined in the
+    /// script. The channel is disconnected after the last event has been
+    /// received and the source unblocked, which allows the event loop to end:
+    ///
+    /// ```
+    /// use termimad::{*, crossterm::event::KeyCode};
+    ///
+    /// let script = EventScript::new()
+    ///     .key(KeyCode::Down)
+    ///     .click(3, 2)
+    ///     .wait_millis(100)
+    ///     .click(3, 2);
+    /// let event_source = EventSource::with_script(script, EventSourceOptions::default());
+    /// let rx = event_source.receiver();
+    /// let mut double_clicks = 0;
+    /// while let Ok(timed_event) = rx.recv() {
+    ///     if timed_event.double_click {
+    ///         double_clicks += 1;
+    ///     }
+    ///     event_source.unblock(false);
+    /// }
+    /// assert_eq!(double_clicks, 2); // the down and up of the second click
+    /// ```
+    pub fn with_script(script: EventScript, options: EventSourceOptions) -> Self {
+        let (source, mut processor) = Self::build(options, Combiner::default(), false, false);
+        let start = Instant::now();
+        thread::spawn(move || {
+            for (delay, ct_event) in script.events {
+                if processor.process(ct_event, start + delay) {
+                    return;
                 }
-                if let Event::Mouse(MouseEvent {
-                    kind, column, row, ..
-                }) = timed_event.event
-                {
-                    if matches!(
-                        kind,
-                        MouseEventKind::Down(MouseButton::Left)
-                            | MouseEventKind::Up(MouseButton::Left)
-                    ) {
-                        if let Some(TimedClick { time, x, y }) = last_up {
-                            if column == x
-                                && row == y
-                                && timed_event.time - time < DOUBLE_CLICK_MAX_DURATION
-                            {
-                                timed_event.double_click = true;
-                            }
-                        }
-                        if kind == MouseEventKind::Up(MouseButton::Left) {
-                            last_up = Some(TimedClick {
-                                time: timed_event.time,
-                                x: column,
-                                y: row,
-                            });
-                        }
-                    }
+            }
+        });
+        source
+    }
+    /// create a source replaying a recording, without reading the
+    /// terminal nor changing its mode.
+    ///
+    /// The events are sent as they were recorded, with their key
+    /// combinations and double click flags, and with times
+    /// keeping their original intervals, whatever the timing of the replay.
+    ///
+    /// As for a scripted source, the channel is disconnected after
+    /// the last event.
+    pub fn with_recording(
+        recording: EventRecording,
+        timing: ReplayTiming,
+        options: EventSourceOptions,
+    ) -> Self {
+        let (source, processor) = Self::build(options, Combiner::default(), false, false);
+        let start = Instant::now();
+        thread::spawn(move || {
+            let mut previous = Duration::ZERO;
+            for recorded in recording.events {
+                let delay = Duration::from_millis(recorded.millis);
+                let wait = timing.wait(delay.saturating_sub(previous));
+                if wait > Duration::ZERO {
+                    thread::sleep(wait);
                 }
-                // we send the event to the receiver in the main event loop
-                if send_and_wait(timed_event) {
+                previous = delay;
+                let timed_event = TimedEvent {
+                    time: start + delay,
+                    event: recorded.event,
+                    double_click: recorded.double_click,
+                    key_combination: recorded.key_combination,
+                };
+                if processor.send_and_wait(timed_event) {
                     return;
                 }
             }
         });
-        Ok(EventSource {
+        source
+    }
+    fn build(
+        options: EventSourceOptions,
+        mut combiner: Combiner,
+        is_combining_keys: bool,
+        raw_mode: bool,
+    ) -> (Self, EventProcessor) {
+        combiner.set_mandate_modifier_for_multiple_keys(options.mandate_modifier_for_multiple_keys);
+        let (tx_events, rx_events) = unbounded();
+        let (tx_seqs, rx_seqs) = bounded(ESCAPE_SEQUENCE_CHANNEL_SIZE);
+        let (tx_quit, rx_quit) = unbounded();
+        let event_count = Arc::new(AtomicUsize::new(0));
+        let recorder = if options.record {
+            Some(Recorder::default())
+        } else {
+            None
+        };
+        let recorder = Arc::new(Mutex::new(recorder));
+        let processor = EventProcessor {
+            options,
+            combiner,
+            last_up: None,
+            current_escape_sequence: None,
+            tx_events,
+            tx_seqs,
+            rx_quit,
+            event_count: Arc::clone(&event_count),
+            recorder: Arc::clone(&recorder),
+        };
+        let source = EventSource {
             is_combining_keys,
+            raw_mode,
             rx_events,
             rx_seqs,
             tx_quit,
             event_count,
-        })
+            recorder,
+        };
+        (source, processor)
     }
 
     /// either start listening again, or quit, depending on the passed bool.
     /// It's mandatory to call this with quit=true at end for a proper ending
     /// of the thread (and its resources)
     pub fn unblock(&self, quit: bool) {
-        self.tx_quit.send(quit).unwrap();
+        // the thread may already be gone, for example at the end of a script
+        let _ = self.tx_quit.send(quit);
     }
 
     /// return a shared reference to the event count. Other threads can
@@ -288,6 +432,21 @@ impl EventSource {
         Arc::clone(&self.event_count)
     }
 
+    /// start recording the emitted events, dropping any current recording
+    pub fn start_recording(&self) {
+        *self.recorder.lock().unwrap() = Some(Recorder::default());
+    }
+
+    /// stop recording and return the recorded events, if the
+    /// source was recording
+    pub fn stop_recording(&self) -> Option<EventRecording> {
+        self.recorder
+            .lock()
+            .unwrap()
+            .take()
+            .map(Recorder::into_recording)
+    }
+
     /// return a new receiver for the channel emmiting events
     pub fn receiver(&self) -> Receiver<TimedEvent> {
         self.rx_events.clone()
@@ -304,6 +463,8 @@ impl EventSource {
 
 impl Drop for EventSource {
     fn drop(&mut self) {
-        terminal::disable_raw_mode().unwrap();
+        if self.raw_mode {
+            terminal::disable_raw_mode().unwrap();
+        }
     }
 }
@@ -1,10 +1,18 @@
 mod escape_sequence;
+mod event_recording;
+mod event_script;
 mod event_source;
 mod tick_beam;
 mod timed_event;
 
 pub use {
     escape_sequence::EscapeSequence,
+    event_recording::{
+        EventRecording,
+        RecordedEvent,
+        ReplayTiming,
+    },
+    event_script::EventScript,
     event_source::{
         EventSource,
         EventSourceOptions,
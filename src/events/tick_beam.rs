@@ -128,6 +128,12 @@ impl<P: Copy + Send + 'static> Ticker<P> {
     }
 }
 
+impl<P> Default for Ticker<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<P> Drop for Ticker<P> {
     fn drop(&mut self) {
         self.stop_all_beams();
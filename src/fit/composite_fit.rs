@@ -1,10 +1,11 @@
 use {
-    crate::*,
-    minimad::*,
-    unicode_width::{
-        UnicodeWidthChar,
-        UnicodeWidthStr,
+    crate::{
+        compound_range::SourcedCompound,
+        *,
     },
+    minimad::*,
+    unicode_segmentation::UnicodeSegmentation,
+    unicode_width::UnicodeWidthStr,
 };
 
 pub static ELLIPSIS: &str = "…";
@@ -34,16 +35,18 @@ impl Default for Fitter {
     }
 }
 
+/// An extended grapheme cluster, the smallest part which may be
+/// removed without breaking a visible character
 #[derive(Debug, Clone, Copy)]
-struct CharInfo {
+struct GraphemeInfo {
     byte_idx: usize,
-    width: usize, // virer
+    width: usize,
 }
-fn str_char_infos(s: &str) -> Vec<CharInfo> {
-    s.char_indices()
-        .map(|(byte_idx, char)| CharInfo {
+fn str_grapheme_infos(s: &str) -> Vec<GraphemeInfo> {
+    s.grapheme_indices(true)
+        .map(|(byte_idx, grapheme)| GraphemeInfo {
             byte_idx,
-            width: char.width().unwrap_or(0),
+            width: grapheme.width(),
         })
         .collect()
 }
@@ -52,14 +55,14 @@ fn str_char_infos(s: &str) -> Vec<CharInfo> {
 struct Zone {
     compound_idx: usize,
     byte_start_idx: usize,
-    char_infos: Vec<CharInfo>,
-    removable_width: usize, // cell width of string minus one character each end
+    grapheme_infos: Vec<GraphemeInfo>,
+    removable_width: usize, // cell width of string minus one grapheme each end
 }
 impl Zone {
-    fn token(compounds: &[Compound], min_removable_width: usize) -> Vec<Zone> {
+    fn token(compounds: &[SourcedCompound], min_removable_width: usize) -> Vec<Zone> {
         let mut zones = Vec::new();
         for (compound_idx, compound) in compounds.iter().enumerate() {
-            let s = compound.src;
+            let s = compound.compound.src;
             if s.len() < min_removable_width + 2 {
                 continue;
             }
@@ -71,11 +74,11 @@ impl Zone {
                             let zs = &s[byte_start_idx..byte_idx];
                             let removable_width = zs.width();
                             if removable_width >= min_removable_width {
-                                let char_infos = str_char_infos(zs);
+                                let grapheme_infos = str_grapheme_infos(zs);
                                 zones.push(Zone {
                                     compound_idx,
                                     byte_start_idx,
-                                    char_infos,
+                                    grapheme_infos,
                                     removable_width,
                                 });
                             }
@@ -92,11 +95,11 @@ impl Zone {
                     let zs = &s[byte_start_idx..];
                     let removable_width = zs.width();
                     if removable_width >= min_removable_width {
-                        let char_infos = str_char_infos(zs);
+                        let grapheme_infos = str_grapheme_infos(zs);
                         zones.push(Zone {
                             compound_idx,
                             byte_start_idx,
-                            char_infos,
+                            grapheme_infos,
                             removable_width,
                         });
                     }
@@ -105,23 +108,24 @@ impl Zone {
         }
         zones
     }
-    fn biggest_token(compounds: &[Compound], min_removable_width: usize) -> Option<Zone> {
+    fn biggest_token(compounds: &[SourcedCompound], min_removable_width: usize) -> Option<Zone> {
         Zone::token(compounds, min_removable_width)
             .drain(..)
             .max_by_key(|z| z.removable_width)
     }
     /// make a zone from each compound large enough
-    fn compounds(compounds: &[Compound], min_removable_width: usize) -> Vec<Zone> {
+    fn compounds(compounds: &[SourcedCompound], min_removable_width: usize) -> Vec<Zone> {
         compounds
             .iter()
             .enumerate()
             .filter_map(|(compound_idx, compound)| {
-                let char_infos = str_char_infos(compound.src);
-                if char_infos.len() < 2 + min_removable_width {
+                let compound = &compound.compound;
+                let grapheme_infos = str_grapheme_infos(compound.src);
+                if grapheme_infos.len() < 2 + min_removable_width {
                     return None;
                 }
                 let removable = &compound.src
-                    [char_infos[1].byte_idx..char_infos[char_infos.len() - 1].byte_idx];
+                    [grapheme_infos[1].byte_idx..grapheme_infos[grapheme_infos.len() - 1].byte_idx];
                 let removable_width = removable.width();
                 if removable_width < min_removable_width {
                     None
@@ -129,54 +133,57 @@ impl Zone {
                     Some(Zone {
                         compound_idx,
                         byte_start_idx: 0,
-                        char_infos,
+                        grapheme_infos,
                         removable_width,
                     })
                 }
             })
             .collect()
     }
-    fn biggest_compound(compounds: &[Compound], min_removable_width: usize) -> Option<Zone> {
+    fn biggest_compound(compounds: &[SourcedCompound], min_removable_width: usize) -> Option<Zone> {
         Zone::compounds(compounds, min_removable_width)
             .drain(..)
             .max_by_key(|z| z.removable_width)
     }
     /// return the gain (that is the removed minus 1 for the ellipsis length)
-    fn cut(&self, compounds: &mut Vec<Compound>, to_remove: usize) -> usize {
+    fn cut(&self, compounds: &mut Vec<SourcedCompound>, to_remove: usize) -> usize {
         if self.removable_width < 2 {
             return 0;
         }
         let compound = &compounds[self.compound_idx];
-        let len = self.char_infos.len();
-        let mut start_char_idx = len / 2;
-        let mut end_char_idx = start_char_idx;
+        let len = self.grapheme_infos.len();
+        let mut start_idx = len / 2;
+        let mut end_idx = start_idx;
         let mut removed_width = 0;
         loop {
             // we alternatively grow left and right
-            if (end_char_idx - start_char_idx) % 2 == 0 {
-                if end_char_idx + 1 >= len {
+            if (end_idx - start_idx) % 2 == 0 {
+                if end_idx + 1 >= len {
                     break;
                 }
-                end_char_idx += 1;
+                end_idx += 1;
             } else {
-                if start_char_idx <= 1 {
+                if start_idx <= 1 {
                     break;
                 }
-                start_char_idx -= 1;
+                start_idx -= 1;
             }
-            let start_byte_idx = self.byte_start_idx + self.char_infos[start_char_idx].byte_idx;
-            let end_byte_idx = self.byte_start_idx + self.char_infos[end_char_idx].byte_idx;
-            removed_width = (compound.src[start_byte_idx..end_byte_idx]).width();
+            let start_byte_idx = self.byte_start_idx + self.grapheme_infos[start_idx].byte_idx;
+            let end_byte_idx = self.byte_start_idx + self.grapheme_infos[end_idx].byte_idx;
+            removed_width = (compound.compound.src[start_byte_idx..end_byte_idx]).width();
             if removed_width >= to_remove {
                 break;
             }
         }
-        let start_byte_idx = self.byte_start_idx + self.char_infos[start_char_idx].byte_idx;
-        let end_byte_idx = self.byte_start_idx + self.char_infos[end_char_idx].byte_idx;
+        let start_byte_idx = self.byte_start_idx + self.grapheme_infos[start_idx].byte_idx;
+        let end_byte_idx = self.byte_start_idx + self.grapheme_infos[end_idx].byte_idx;
         let head = compound.sub(0, start_byte_idx);
         let tail = compound.tail(end_byte_idx);
         compounds[self.compound_idx] = head;
-        compounds.insert(self.compound_idx + 1, Compound::raw_str(ELLIPSIS));
+        compounds.insert(
+            self.compound_idx + 1,
+            SourcedCompound::inserted(Compound::raw_str(ELLIPSIS)),
+        );
         compounds.insert(self.compound_idx + 2, tail);
 
         removed_width - 1
@@ -204,17 +211,18 @@ impl Fitter {
         if fc.visible_length <= max_width {
             return;
         } else if max_width == 0 {
-            fc.compounds.clear();
+            fc.set_sourced_compounds(Vec::new());
             fc.visible_length = 0;
             return;
         } else if max_width == 1 {
-            fc.compounds.clear();
-            fc.compounds.push(Compound::raw_str(ELLIPSIS));
+            let ellipsis = SourcedCompound::inserted(Compound::raw_str(ELLIPSIS));
+            fc.set_sourced_compounds(vec![ellipsis]);
             fc.visible_length = 1;
             return;
         }
 
         let mut excess = fc.visible_length - max_width;
+        let mut compounds = fc.sourced_compounds();
 
         // note: computing all zones once would be faster but would involve either
         // recomputing compound_idx ou finding another index scheme
@@ -223,8 +231,8 @@ impl Fitter {
             // cutting in the middle of big no space parts
             while excess > 0 {
                 let mut gain = 0;
-                if let Some(zone) = Zone::biggest_token(&fc.compounds, 3) {
-                    gain = zone.cut(&mut fc.compounds, excess + 1);
+                if let Some(zone) = Zone::biggest_token(&compounds, 3) {
+                    gain = zone.cut(&mut compounds, excess + 1);
                 }
                 if gain == 0 {
                     break;
@@ -239,8 +247,8 @@ impl Fitter {
                 let mut gain = 0;
                 // we'll look for zones of removable width at least 2
                 // (because we put the ellipsis in place)
-                if let Some(zone) = Zone::biggest_compound(&fc.compounds, 2) {
-                    gain = zone.cut(&mut fc.compounds, excess + 1);
+                if let Some(zone) = Zone::biggest_compound(&compounds, 2) {
+                    gain = zone.cut(&mut compounds, excess + 1);
                 }
                 if gain == 0 {
                     break;
@@ -250,11 +258,11 @@ impl Fitter {
         }
 
         if excess == 0 {
+            fc.set_sourced_compounds(compounds);
             fc.recompute_width(skin);
             return;
         }
 
-        let compounds = &mut fc.compounds;
         // we'll have to compensate with 1 or 2 ellipsis, so the "excess" is
         // increased accordingly we increase
         let (mut excess_left, mut excess_right) = match self.align {
@@ -274,40 +282,39 @@ impl Fitter {
         if excess_left > 0 {
             // left truncating
             while excess_left > 0 && !compounds.is_empty() {
-                let compound = &mut compounds[0];
-                let char_infos = str_char_infos(compound.src);
-                let mut last_removed_char_idx = 0;
+                let grapheme_infos = str_grapheme_infos(compounds[0].compound.src);
+                let mut last_removed_idx = 0;
                 let mut removed_width = 0;
                 loop {
-                    removed_width += char_infos[last_removed_char_idx].width;
-                    if removed_width >= excess_left || last_removed_char_idx + 1 == char_infos.len()
+                    removed_width += grapheme_infos[last_removed_idx].width;
+                    if removed_width >= excess_left || last_removed_idx + 1 == grapheme_infos.len()
                     {
                         break;
                     }
-                    last_removed_char_idx += 1;
+                    last_removed_idx += 1;
                 }
-                if last_removed_char_idx + 1 == char_infos.len() {
+                if last_removed_idx + 1 == grapheme_infos.len() {
                     // we remove the whole compound
                     compounds.remove(0);
                     excess_left -= removed_width.min(excess_left);
                 } else {
                     // we cut the left part
-                    compound.src = &compound.src[char_infos[last_removed_char_idx + 1].byte_idx..];
+                    compounds[0] = compounds[0].tail(grapheme_infos[last_removed_idx + 1].byte_idx);
                     excess_left = 0;
                 }
             }
-            compounds.insert(0, Compound::raw_str(ELLIPSIS));
+            compounds.insert(0, SourcedCompound::inserted(Compound::raw_str(ELLIPSIS)));
         }
 
         if excess_right > 0 {
             // right truncating
             while excess_right > 0 && !compounds.is_empty() {
                 let last_idx = compounds.len() - 1;
-                let compound = &mut compounds[last_idx];
-                let char_infos = str_char_infos(compound.src);
+                let src = compounds[last_idx].compound.src;
+                let grapheme_infos = str_grapheme_infos(src);
                 let mut removed_width = 0;
-                let mut end_byte_idx = compound.src.len();
-                for ci in char_infos.iter().rev() {
+                let mut end_byte_idx = src.len();
+                for ci in grapheme_infos.iter().rev() {
                     end_byte_idx = ci.byte_idx;
                     removed_width += ci.width;
                     if removed_width >= excess_right {
@@ -320,13 +327,14 @@ impl Fitter {
                     excess_right -= removed_width.min(excess_right);
                 } else {
                     // we cut the right part
-                    compound.src = &compound.src[..end_byte_idx];
+                    compounds[last_idx] = compounds[last_idx].sub(0, end_byte_idx);
                     excess_right = 0;
                 }
             }
-            compounds.push(Compound::raw_str(ELLIPSIS));
+            compounds.push(SourcedCompound::inserted(Compound::raw_str(ELLIPSIS)));
         }
 
+        fc.set_sourced_compounds(compounds);
         fc.recompute_width(skin);
     }
 }
@@ -7,6 +7,7 @@ use {
         *,
     },
     std::borrow::Cow,
+    unicode_segmentation::UnicodeSegmentation,
     unicode_width::UnicodeWidthChar,
 };
 
@@ -80,14 +81,14 @@ where
         }
         Ok(())
     }
-    /// a "g_string" is a "gentle" one: each char takes one column on screen.
+    /// a "g_string" is a "gentle" one: each grapheme takes one column on screen.
     /// This function must thus not be used for unknown strings.
     pub fn queue_unstyled_g_string(&mut self, mut s: String) -> Result<(), Error> {
         if self.is_full() {
             return Ok(());
         }
         let mut len = 0;
-        for (idx, _) in s.char_indices() {
+        for (idx, _) in s.grapheme_indices(true) {
             len += 1;
             if len > self.allowed {
                 s.truncate(idx);
@@ -100,14 +101,14 @@ where
         self.w.queue(Print(s))?;
         Ok(())
     }
-    /// a "g_string" is a "gentle" one: each char takes one column on screen.
+    /// a "g_string" is a "gentle" one: each grapheme takes one column on screen.
     /// This function must thus not be used for unknown strings.
     pub fn queue_g_string(&mut self, cs: &CompoundStyle, mut s: String) -> Result<(), Error> {
         if self.is_full() {
             return Ok(());
         }
         let mut len = 0;
-        for (idx, _) in s.char_indices() {
+        for (idx, _) in s.grapheme_indices(true) {
             len += 1;
             if len > self.allowed {
                 s.truncate(idx);
@@ -10,12 +10,9 @@ mod tbl_fit;
 pub mod wrap;
 
 use {
-    crate::crossterm::{
-        style::{
-            Color,
-            SetBackgroundColor,
-        },
-        QueueableCommand,
+    crate::{
+        crossterm::style::Color,
+        CompoundStyle,
     },
     minimad::once_cell::sync::Lazy,
 };
@@ -37,7 +34,7 @@ pub fn fill_bg<W>(w: &mut W, len: usize, bg: Color) -> Result<(), Error>
 where
     W: std::io::Write,
 {
-    w.queue(SetBackgroundColor(bg))?;
+    CompoundStyle::with_bg(bg).queue_bg(w)?;
     SPACE_FILLING.queue_unstyled(w, len)?;
     Ok(())
 }
@@ -1,6 +1,7 @@
 use {
     std::borrow::Cow,
-    unicode_width::UnicodeWidthChar,
+    unicode_segmentation::UnicodeSegmentation,
+    unicode_width::UnicodeWidthStr,
 };
 
 pub static TAB_REPLACEMENT: &str = "  ";
@@ -9,7 +10,9 @@ pub static TAB_REPLACEMENT: &str = "  ";
 /// width in cols.
 ///
 /// The implementation here properly takes into account
-/// the width of special characters.
+/// the width of special characters, and never cuts an
+/// extended grapheme cluster (an emoji sequence, a letter
+/// with its combining accents, etc.).
 ///
 /// Backspaces are considered as having a width of -1.
 ///
@@ -27,25 +30,25 @@ impl StrFit {
         let mut bytes_count = 0;
         let mut cols_count: i32 = 0;
         let mut has_tab = false;
-        for (idx, c) in s.char_indices() {
-            let char_width: i32 = match c {
-                '\t' => {
+        for (idx, g) in s.grapheme_indices(true) {
+            let grapheme_width: i32 = match g {
+                "\t" => {
                     // tab
                     has_tab = true;
                     TAB_REPLACEMENT.len() as i32
                 }
-                '\x08' => {
+                "\x08" => {
                     // backspace
                     -1
                 }
-                _ => UnicodeWidthChar::width(c).map(|w| w as i32).unwrap_or(0),
+                _ => g.width() as i32,
             };
-            let next_str_width = cols_count + char_width;
+            let next_str_width = cols_count + grapheme_width;
             if next_str_width > 0 && next_str_width as usize > cols_max {
                 break;
             }
             cols_count = next_str_width;
-            bytes_count = idx + c.len_utf8();
+            bytes_count = idx + g.len();
         }
         Self {
             bytes_count,
@@ -82,7 +85,7 @@ impl StrFit {
     /// if it's very big
     /// In case there's no tab in the input string, we can return a pointer over
     /// part of the original str)
-    pub fn make_cow(s: &str, cols_max: usize) -> (Cow<str>, usize) {
+    pub fn make_cow(s: &str, cols_max: usize) -> (Cow<'_, str>, usize) {
         let fit = StrFit::from(s, cols_max);
         if fit.has_tab {
             // we can't just borrow, as we insert chars
@@ -121,4 +124,16 @@ mod fitting_count_tests {
         assert_eq!(StrFit::count_fitting(ja, 4), (6, 4));
         assert_eq!(StrFit::count_fitting(ja, 5), (6, 4));
     }
+
+    #[test]
+    fn test_count_fitting_graphemes() {
+        let family = "👨\u{200d}👩\u{200d}👧"; // one grapheme of 5 chars, 2 columns
+        assert_eq!(StrFit::count_fitting(family, 1), (0, 0));
+        assert_eq!(StrFit::count_fitting(family, 2), (family.len(), 2));
+        let flags = "🇫🇷🇮🇹"; // two flags of two regional indicators
+        assert_eq!(StrFit::count_fitting(flags, 3), (8, 2));
+        let accents = "e\u{301}e\u{301}"; // two e with a combining acute accent
+        assert_eq!(StrFit::count_fitting(accents, 1), (3, 1));
+        assert_eq!(StrFit::make_string("🇫🇷🇮🇹", 3), ("🇫🇷".to_string(), 2));
+    }
 }
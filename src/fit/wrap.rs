@@ -1,32 +1,59 @@
 #[allow(unused_imports)]
 use {
     crate::{
+        compound_range::SourcedCompound,
         minimad::*,
         *,
     },
-    unicode_width::UnicodeWidthStr,
+    unicode_width::{
+        UnicodeWidthChar,
+        UnicodeWidthStr,
+    },
 };
 
-/// build a composite which can be a new line after wrapping.
-fn follow_up_composite<'s>(fc: &FmtComposite<'s>, skin: &MadSkin) -> FmtComposite<'s> {
-    let kind = match fc.kind {
+/// minimal number of columns left for the code, after the indentation
+/// and the continuation mark, on the continuation of a wrapped code line
+const MIN_CODE_FOLLOW_UP_WIDTH: usize = 3;
+
+/// return the kind of the composites following the first one
+/// when wrapping a composite in the given width
+fn follow_up_kind(fc: &FmtComposite<'_>, width: usize, skin: &MadSkin) -> CompositeKind {
+    match fc.kind {
         CompositeKind::ListItem(l) => CompositeKind::ListItemFollowUp(l),
-        k => k,
-    };
-    let visible_length = match kind {
-        CompositeKind::ListItemFollowUp(l)
-            if skin.list_items_indentation_mode == ListItemsIndentationMode::Block =>
-        {
-            2 + l as usize
+        CompositeKind::TaskListItem(l, _) => CompositeKind::ListItemFollowUp(l),
+        CompositeKind::OrderedListItem(l, n) => CompositeKind::OrderedListItemFollowUp(l, n),
+        CompositeKind::Code => {
+            // the indentation is kept, but there must be room for
+            // the continuation mark and some code
+            let indent: usize = fc
+                .compounds
+                .iter()
+                .flat_map(|c| c.src.chars())
+                .take_while(|c| c.is_whitespace())
+                .map(|c| c.width().unwrap_or(0))
+                .sum();
+            let min_width = skin.code_continuation_width() + MIN_CODE_FOLLOW_UP_WIDTH;
+            CompositeKind::CodeFollowUp(indent.min(width.saturating_sub(min_width)))
         }
-        CompositeKind::Quote => 2,
-        _ => 0,
-    };
+        CompositeKind::Footnote(_) | CompositeKind::StackedCell(_) => CompositeKind::Paragraph,
+        k => k,
+    }
+}
+
+/// build a composite which can be a new line after wrapping.
+///
+/// Its links, code tokens and marks are set with its compounds,
+/// once the line is complete.
+fn follow_up_composite<'s>(
+    fc: &FmtComposite<'s>,
+    kind: CompositeKind,
+    skin: &MadSkin,
+) -> FmtComposite<'s> {
     FmtComposite {
         kind,
-        compounds: Vec::new(),
-        visible_length,
+        visible_length: composite_kind_widths(kind, skin).1,
         spacing: fc.spacing,
+        ..FmtComposite::new()
     }
 }
 
@@ -36,7 +63,7 @@ pub fn composite_kind_widths(composite_kind: CompositeKind, skin: &MadSkin) -> (
     match composite_kind {
         CompositeKind::Paragraph => (0, 0),
         CompositeKind::Header(_) => (0, 0),
-        CompositeKind::ListItem(depth) => {
+        CompositeKind::ListItem(depth) | CompositeKind::TaskListItem(depth, _) => {
             let indent = 2 + depth as usize;
             match skin.list_items_indentation_mode {
                 ListItemsIndentationMode::FirstLineOnly => (indent, 0),
@@ -50,8 +77,30 @@ pub fn composite_kind_widths(composite_kind: CompositeKind, skin: &MadSkin) -> (
                 ListItemsIndentationMode::Block => (indent, indent),
             }
         }
+        CompositeKind::OrderedListItem(depth, number) => {
+            let indent = skin.ordered_list_item_indent(depth, number);
+            match skin.list_items_indentation_mode {
+                ListItemsIndentationMode::FirstLineOnly => (indent, 0),
+                ListItemsIndentationMode::Block => (indent, indent),
+            }
+        }
+        CompositeKind::OrderedListItemFollowUp(depth, number) => {
+            let indent = skin.ordered_list_item_indent(depth, number);
+            match skin.list_items_indentation_mode {
+                ListItemsIndentationMode::FirstLineOnly => (0, 0),
+                ListItemsIndentationMode::Block => (indent, indent),
+            }
+        }
         CompositeKind::Code => (0, 0),
-        CompositeKind::Quote => (2, 2),
+        CompositeKind::CodeFollowUp(indent) => {
+            let width = indent + skin.code_continuation_width();
+            (width, width)
+        }
+        CompositeKind::Quote(depth) => (2 * depth as usize, 2 * depth as usize),
+        CompositeKind::Admonition(_) | CompositeKind::AdmonitionTitle(_) => (2, 2),
+        CompositeKind::Footnote(_) | CompositeKind::StackedCell(_) => {
+            (skin.visible_composite_length(composite_kind, &[]), 0)
+        }
     }
 }
 
@@ -70,55 +119,122 @@ pub fn hard_wrap_composite<'s, 'c>(
     }
     debug_assert!(src_composite.visible_length > width); // or we shouldn't be called
     let mut composites: Vec<FmtComposite<'s>> = Vec::new();
-    let (first_width, other_widths) = composite_kind_widths(src_composite.kind, skin);
+    let follow_up_kind = follow_up_kind(src_composite, width, skin);
+    let first_width = composite_kind_widths(src_composite.kind, skin).0;
+    let other_widths = composite_kind_widths(follow_up_kind, skin).1;
     let mut dst_composite = FmtComposite {
         kind: src_composite.kind,
-        compounds: Vec::new(),
         visible_length: first_width,
         spacing: src_composite.spacing,
+        ..FmtComposite::new()
     };
+    // the compounds of the line being built
+    let mut dst_compounds: Vec<SourcedCompound<'s>> = Vec::new();
 
     // Strategy 1:
     // we try to optimize for a quite frequent case: two parts with nothing or just space in
     // between
     let compounds = &src_composite.compounds;
+    let compound_width = |idx: usize| compounds[idx].src.width() + src_composite.width_after(idx);
+    let last = compounds.len().saturating_sub(1);
     if (
         // clean cut of 2
         compounds.len() == 2
-            && compounds[0].src.width() + first_width <= width
-            && compounds[1].src.width() + other_widths <= width
+            && compound_width(0) + first_width <= width
+            && compound_width(1) + other_widths <= width
     ) || (
         // clean cut of 3
         compounds.len() == 3
-            && compounds[0].src.width() + first_width <= width
-            && compounds[2].src.width() + other_widths <= width
+            && compound_width(0) + first_width <= width
+            && compound_width(2) + other_widths <= width
             && compounds[1].src.chars().all(char::is_whitespace)
     ) {
-        dst_composite.add_compound(compounds[0].clone());
-        let mut new_dst_composite = follow_up_composite(&dst_composite, skin);
+        dst_composite.visible_length += compound_width(0);
+        dst_composite.set_compounds_from(
+            src_composite,
+            vec![SourcedCompound::new(0, compounds[0].clone())],
+        );
+        let mut new_dst_composite = follow_up_composite(&dst_composite, follow_up_kind, skin);
+        new_dst_composite.wrap_joint = Some(if compounds.len() == 3 {
+            WrapJoint::Space
+        } else {
+            WrapJoint::Direct
+        });
         composites.push(dst_composite);
-        new_dst_composite.add_compound(compounds[compounds.len() - 1].clone());
+        new_dst_composite.visible_length += compound_width(last);
+        new_dst_composite.set_compounds_from(
+            src_composite,
+            vec![SourcedCompound::new(last, compounds[last].clone())],
+        );
         composites.push(new_dst_composite);
         return Ok(composites);
     }
 
-    let mut tokens = tokenize(&src_composite.compounds, width - first_width);
+    let mut tokens = tokenize(
+        &src_composite.compounds,
+        width - first_width.max(other_widths),
+        skin.hyphenator,
+    )
+    .into_iter()
+    .peekable();
     // Strategy 2:
-    // we try to cut along tokens, using spaces to break
-    for token in tokens.drain(..) {
+    // we try to cut along tokens, using spaces, hyphens, and other
+    // break opportunities to break
+    // whether the previous token is a place where the line may be
+    // broken with a hyphen
+    let mut hyphen = false;
+    // whether blank tokens were skipped since the last written token
+    let mut skipped_blank = false;
+    while let Some(token) = tokens.next() {
+        if token.hyphen {
+            hyphen = true;
+            continue;
+        }
+        // if the line may be broken just after this token, there must
+        // be room for the hyphen
+        let hyphen_width = match tokens.peek() {
+            Some(next) if next.hyphen => 1,
+            _ => 0,
+        };
+        // a footnote mark must stay with the end of the text of its link
+        let mut token_width = token.width;
+        if token.ends_compound() {
+            token_width += src_composite.width_after(token.compound_idx);
+        }
         // TODO: does that really take first_width into account ?
-        if dst_composite.visible_length + token.width > width {
+        if dst_composite.visible_length + token_width + hyphen_width > width {
             if !token.blank {
                 // we skip blank composite at line change
-                let mut repl_composite = follow_up_composite(&dst_composite, skin);
+                if hyphen {
+                    // the hyphen isn't in the source, it's written after
+                    // the compounds, with the style of the last one
+                    dst_composite.hyphenated = true;
+                    dst_composite.visible_length += 1;
+                }
+                let mut repl_composite = follow_up_composite(&dst_composite, follow_up_kind, skin);
                 std::mem::swap(&mut dst_composite, &mut repl_composite);
+                let line_compounds = std::mem::take(&mut dst_compounds);
+                repl_composite.set_compounds_from(src_composite, line_compounds);
                 composites.push(repl_composite);
-                dst_composite.add_compound(token.to_compound());
+                dst_composite.wrap_joint = Some(if skipped_blank {
+                    WrapJoint::Space
+                } else {
+                    WrapJoint::Direct
+                });
+                dst_composite.visible_length += token_width;
+                dst_compounds.push(token.to_sourced_compound());
+                skipped_blank = false;
+            } else {
+                skipped_blank = true;
             }
         } else {
-            dst_composite.add_compound(token.to_compound());
+            dst_composite.visible_length += token_width;
+            dst_compounds.push(token.to_sourced_compound());
+            skipped_blank = false;
         }
+        hyphen = false;
     }
+    dst_composite.set_compounds_from(src_composite, dst_compounds);
     composites.push(dst_composite);
     Ok(composites)
 }
@@ -138,10 +254,23 @@ pub fn hard_wrap_lines<'s>(
     for src_line in src_lines.drain(..) {
         if let FmtLine::Normal(fc) = src_line {
             let (left_margin, right_margin) = skin.line_style(fc.kind).margins_in(Some(width));
-            if fc.visible_length + left_margin + right_margin <= width {
+            let unbroken_length = fc.visible_length - line_break::count_soft_hyphens(&fc);
+            if unbroken_length + left_margin + right_margin <= width {
+                let mut fc = fc;
+                line_break::remove_soft_hyphens(&mut fc, skin);
                 lines.push(FmtLine::Normal(fc));
             } else {
-                for fc in hard_wrap_composite(&fc, width - left_margin - right_margin, skin)? {
+                let width = width - left_margin - right_margin;
+                let mut composites = hard_wrap_composite(&fc, width, skin)?;
+                let line_style = skin.line_style(fc.kind);
+                let is_code = matches!(fc.kind, CompositeKind::Code | CompositeKind::CodeFollowUp(_));
+                if line_style.justified && !is_code {
+                    let last = composites.len() - 1;
+                    for fc in &mut composites[..last] {
+                        fc.justify(width);
+                    }
+                }
+                for fc in composites {
                     lines.push(FmtLine::Normal(fc));
                 }
             }
@@ -271,4 +400,116 @@ mod wrap_tests {
             let _text = FmtText::from(&skin, md, Some(w));
         }
     }
+
+    #[test]
+    /// check nested quotes are rendered with a mark per level, also on wrapped lines
+    fn check_nested_quotes() {
+        let mut skin = MadSkin::no_style();
+        skin.quote_mark = StyledChar::nude('>');
+        skin.nested_quote_marks = vec![StyledChar::nude('|')];
+        let md = "> simple\n>> nested quote, wrapped\n> > nested too\n>>> deeper";
+        let text = FmtText::from(&skin, md, Some(20)).to_string();
+        assert_eq!(
+            text,
+            "> simple\n> | nested quote, \n> | wrapped\n> | nested too\n> | > deeper\n",
+        );
+    }
+
+    #[test]
+    /// check wrapped code lines keep their indentation, with a continuation mark
+    fn check_code_wrapping() {
+        let mut skin = MadSkin::no_style();
+        skin.code_continuation_mark = StyledChar::nude('>');
+        let md = "```\nfn f() {\n    call(first_argument, second_argument);\n}\n```";
+        let text = FmtText::from(&skin, md, Some(24)).to_string();
+        assert_eq!(
+            text,
+            concat!(
+                "fn f() {                \n",
+                "    call(first_argument,\n",
+                "    > second_argument); \n",
+                "}                       \n",
+            ),
+        );
+    }
+
+    #[test]
+    /// check a wide continuation mark is counted with its width
+    fn check_code_wrapping_with_wide_mark() {
+        let mut skin = MadSkin::no_style();
+        skin.code_continuation_mark = StyledChar::nude('全');
+        let md = "```\n    call(first_argument, second_argument, third_argument);\n```";
+        let text = FmtText::from(&skin, md, Some(20)).to_string();
+        assert!(text.lines().count() > 2);
+        for line in text.lines() {
+            assert_eq!(line.width(), 20, "bad width of {line:?}");
+        }
+    }
+
+    #[test]
+    fn check_justification() {
+        let mut skin = MadSkin::no_style();
+        skin.paragraph = parse_line_style("justify").unwrap();
+        skin.bullet = StyledChar::nude('-');
+        let md = "A short paragraph which is *justified*, but not its last line.\n\
+                  * an item with **bold** words";
+        let text = FmtText::from(&skin, md, Some(20)).to_string();
+        assert_eq!(
+            text,
+            concat!(
+                "A   short  paragraph\n",
+                "which  is justified,\n",
+                "but   not  its  last\n",
+                "line.\n",
+                "- an  item with bold\n",
+                "  words\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn check_justified_link_and_marks() {
+        let mut skin = MadSkin::no_style();
+        skin.paragraph = parse_line_style("justify").unwrap();
+        skin.link_rendering = LinkRendering::Hyperlink;
+        let md = "A [justified link](https://dystroy.org) within a paragraph";
+        let text = FmtText::from(&skin, md, Some(18));
+        let rendered = text.to_string();
+        assert_eq!(
+            strip_ansi(&rendered),
+            "A  justified  link\nwithin a paragraph\n",
+        );
+        // the widened gap stays in the link, written in one hyperlink
+        assert_eq!(rendered.matches("\u{1b}]8;;https").count(), 1);
+        assert!(rendered.contains("justified  link\u{1b}]8;;\u{1b}\\"));
+        // and in the search match
+        let mut search = TextSearch::new(SearchPattern::plain("justified link"));
+        search.update(&text);
+        let Some(FmtLine::Normal(fc)) = search.highlighted_line(0, &text.lines[0]) else {
+            panic!("no highlighted line");
+        };
+        let highlighted: Vec<&str> = fc
+            .compounds
+            .iter()
+            .enumerate()
+            .filter(|(idx, c)| fc.marks.iter().any(|m| m.contains(*idx, c)))
+            .map(|(_, c)| c.src)
+            .collect();
+        assert_eq!(highlighted, vec!["justified", " ", "link"]);
+        assert_eq!(fc.visible_length, 18);
+    }
+
+    #[test]
+    fn check_justified_links_in_parentheses() {
+        let mut skin = MadSkin::no_style();
+        skin.paragraph = parse_line_style("justify").unwrap();
+        skin.link_rendering = LinkRendering::UrlInParentheses;
+        let md = "[bb](u) [dd](v) ccc dddd eeeee ffffff ggggggg hhhhhhhh";
+        let text = FmtText::from(&skin, md, Some(30)).to_string();
+        // the spaces inserted before the URLs don't share their padding
+        assert_eq!(
+            text,
+            "bb  (u)  dd (v) ccc dddd eeeee\nffffff ggggggg hhhhhhhh\n",
+        );
+    }
 }
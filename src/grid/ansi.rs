@@ -0,0 +1,151 @@
+use crate::{
+    crossterm::style::{
+        Attribute,
+        Color,
+    },
+    CompoundStyle,
+};
+
+/// Something read in the stream written to a buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action<'a> {
+    Print(char),
+    /// a "Control Sequence Introducer" sequence, like a cursor move
+    Csi { params: &'a str, end: char },
+    /// an "Operating System Command", like a hyperlink
+    Osc(&'a str),
+    SaveCursor,
+    RestoreCursor,
+    /// an unknown or unsupported escape sequence
+    Ignore,
+}
+
+/// Read the action at the start of `s`, returning it with
+/// its length in bytes, or None if the sequence is incomplete
+pub(crate) fn parse_action(s: &str) -> Option<(Action<'_>, usize)> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    if c != '\u{1b}' {
+        return Some((Action::Print(c), c.len_utf8()));
+    }
+    match chars.next()? {
+        '[' => {
+            for (i, c) in s[2..].char_indices() {
+                if ('\u{40}'..='\u{7e}').contains(&c) {
+                    let action = Action::Csi {
+                        params: &s[2..2 + i],
+                        end: c,
+                    };
+                    return Some((action, 2 + i + 1));
+                }
+                if !('\u{20}'..='\u{3f}').contains(&c) {
+                    return Some((Action::Ignore, 2 + i));
+                }
+            }
+            None
+        }
+        ']' => {
+            // terminated either by BEL or by ST (ESC \)
+            let bel = s.find('\u{7}').map(|i| (i, 1));
+            let st = s.find("\u{1b}\\").map(|i| (i, 2));
+            let (end, len) = match (bel, st) {
+                (Some(bel), Some(st)) => bel.min(st),
+                (Some(end), None) | (None, Some(end)) => end,
+                (None, None) => {
+                    return None;
+                }
+            };
+            Some((Action::Osc(&s[2..end]), end + len))
+        }
+        '7' => Some((Action::SaveCursor, 2)),
+        '8' => Some((Action::RestoreCursor, 2)),
+        c => Some((Action::Ignore, 1 + c.len_utf8())),
+    }
+}
+
+/// The attributes removed by the SGR parameters cancelling them
+fn cancelled_attributes(param: &str) -> &'static [Attribute] {
+    match param {
+        "21" => &[Attribute::Bold],
+        "22" => &[Attribute::Bold, Attribute::Dim],
+        "23" => &[Attribute::Italic, Attribute::Fraktur],
+        "24" => &[
+            Attribute::Underlined,
+            Attribute::DoubleUnderlined,
+            Attribute::Undercurled,
+            Attribute::Underdotted,
+            Attribute::Underdashed,
+        ],
+        "25" => &[Attribute::SlowBlink, Attribute::RapidBlink],
+        "27" => &[Attribute::Reverse],
+        "28" => &[Attribute::Hidden],
+        "29" => &[Attribute::CrossedOut],
+        "54" => &[Attribute::Framed, Attribute::Encircled],
+        "55" => &[Attribute::OverLined],
+        _ => &[],
+    }
+}
+
+/// Return the color of a 3 or 4 bits SGR color parameter
+/// (the code of the black color being given)
+fn basic_color(code: u8, black: u8) -> Option<Color> {
+    if (black..black + 8).contains(&code) {
+        Color::parse_ansi(&format!("5;{}", code - black))
+    } else if (black + 60..black + 68).contains(&code) {
+        Color::parse_ansi(&format!("5;{}", code - black - 60 + 8))
+    } else {
+        None
+    }
+}
+
+/// Modify the style according to the parameters of a SGR
+/// ("Select Graphic Rendition") sequence
+pub(crate) fn apply_sgr(style: &mut CompoundStyle, params: &str) {
+    let mut params = params.split(';');
+    while let Some(param) = params.next() {
+        match param {
+            "" | "0" => *style = CompoundStyle::default(),
+            "38" | "48" | "58" => {
+                let color = match params.next() {
+                    Some("5") => params.next().map(|n| format!("5;{n}")),
+                    Some("2") => {
+                        let rgb: Vec<&str> = params.by_ref().take(3).collect();
+                        Some(format!("2;{}", rgb.join(";")))
+                    }
+                    _ => None,
+                };
+                let color = color.and_then(|c| Color::parse_ansi(&c));
+                match param {
+                    "38" => style.object_style.foreground_color = color,
+                    "48" => style.object_style.background_color = color,
+                    _ => style.object_style.underline_color = color,
+                }
+            }
+            "39" => style.object_style.foreground_color = None,
+            "49" => style.object_style.background_color = None,
+            "59" => style.object_style.underline_color = None,
+            _ => {
+                let cancelled = cancelled_attributes(param);
+                if !cancelled.is_empty() {
+                    for &attr in cancelled {
+                        style.remove_attr(attr);
+                    }
+                    continue;
+                }
+                if let Ok(code) = param.parse::<u8>() {
+                    if let Some(color) = basic_color(code, 30) {
+                        style.set_fg(color);
+                        continue;
+                    }
+                    if let Some(color) = basic_color(code, 40) {
+                        style.set_bg(color);
+                        continue;
+                    }
+                }
+                if let Some(attr) = Attribute::iterator().find(|a| a.sgr() == param) {
+                    style.add_attr(attr);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use {
+    super::{
+        ansi::{
+            self,
+            Action,
+        },
+        cell::WIDE_CHAR_TAIL,
+        Cell,
+    },
+    crate::{
+        Area,
+        CompoundStyle,
+    },
+    std::io,
+    unicode_width::UnicodeWidthChar,
+};
+
+/// A grid of styled cells, which can be written to like a terminal.
+///
+/// Writing to a buffer interprets the cursor moves, clears, and
+/// styling sequences, so any view can be drawn into it:
+///
+/// ```
+/// use termimad::*;
+///
+/// let skin = MadSkin::default();
+/// let area = Area::new(0, 0, 20, 5);
+/// let text = skin.area_text("# Title\nSome *text*", &area);
+/// let mut buffer = Buffer::new(20, 5);
+/// TextView::from(&area, &text).write_on(&mut buffer).unwrap();
+/// assert_eq!(buffer.cell(0, 1).unwrap().ch, 'S');
+/// ```
+#[derive(Debug, Clone)]
+pub struct Buffer {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+    cursor: (u16, u16),
+    saved_cursor: (u16, u16),
+    cursor_visible: bool,
+    /// the style applied to the written chars
+    style: CompoundStyle,
+    /// the end of the written bytes which couldn't be interpreted yet
+    /// (an incomplete escape sequence or UTF-8 char)
+    pending: Vec<u8>,
+}
+
+impl Buffer {
+    /// Create a buffer filled with unstyled spaces
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+            cursor: (0, 0),
+            saved_cursor: (0, 0),
+            cursor_visible: true,
+            style: CompoundStyle::default(),
+            pending: Vec::new(),
+        }
+    }
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+    /// Return the area covered by the buffer
+    pub fn area(&self) -> Area {
+        Area::new(0, 0, self.width, self.height)
+    }
+    /// Return the position (x, y) where the next char will be written
+    pub fn cursor(&self) -> (u16, u16) {
+        self.cursor
+    }
+    /// Tell whether the cursor was hidden by a sequence
+    /// written to the buffer
+    pub fn is_cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+    pub fn set_cursor(&mut self, x: u16, y: u16) {
+        self.cursor = (
+            x.min(self.width.saturating_sub(1)),
+            y.min(self.height.saturating_sub(1)),
+        );
+    }
+    fn idx(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.idx(x, y).map(|idx| &self.cells[idx])
+    }
+    /// Return the cells of a line
+    pub fn row(&self, y: u16) -> &[Cell] {
+        match self.idx(0, y) {
+            Some(start) => &self.cells[start..start + self.width as usize],
+            None => &[],
+        }
+    }
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+    /// Set a cell, taking care of the wide chars partially overwritten
+    pub fn set(&mut self, x: u16, y: u16, ch: char, style: &CompoundStyle) {
+        let Some(idx) = self.idx(x, y) else {
+            return;
+        };
+        if self.cells[idx].is_wide_char_tail() && x > 0 {
+            self.cells[idx - 1].ch = ' ';
+        }
+        if self.cells[idx].width() > 1 && x + 1 < self.width {
+            self.cells[idx + 1].ch = ' ';
+        }
+        self.cells[idx] = Cell::new(ch, style.clone());
+        if ch.width().unwrap_or(0) > 1 && x + 1 < self.width {
+            if self.cells[idx + 1].width() > 1 && x + 2 < self.width {
+                self.cells[idx + 2].ch = ' ';
+            }
+            self.cells[idx + 1] = Cell::new(WIDE_CHAR_TAIL, style.clone());
+        }
+    }
+    /// Write a string at the given position, without wrapping,
+    /// and return the number of columns written.
+    ///
+    /// The cursor isn't moved.
+    pub fn put_str(&mut self, x: u16, y: u16, s: &str, style: &CompoundStyle) -> usize {
+        let mut cx = x;
+        for ch in s.chars() {
+            let w = ch.width().unwrap_or(0) as u16;
+            if w == 0 {
+                continue;
+            }
+            if cx + w > self.width {
+                break;
+            }
+            self.set(cx, y, ch, style);
+            cx += w;
+        }
+        (cx - x) as usize
+    }
+    /// Fill an area with a styled char
+    pub fn fill(&mut self, area: &Area, ch: char, style: &CompoundStyle) {
+        let w = ch.width().unwrap_or(1).max(1) as u16;
+        for y in area.top..area.bottom().min(self.height) {
+            let mut x = area.left;
+            while x + w <= area.right().min(self.width) {
+                self.set(x, y, ch, style);
+                x += w;
+            }
+        }
+    }
+    /// Reset all cells to unstyled spaces
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = Cell::default();
+        }
+    }
+    /// Resize the buffer, keeping the content which still fits
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let mut cells = vec![Cell::default(); width as usize * height as usize];
+        for y in 0..height.min(self.height) {
+            for x in 0..width.min(self.width) {
+                cells[y as usize * width as usize + x as usize] =
+                    self.cells[y as usize * self.width as usize + x as usize].clone();
+            }
+        }
+        self.cells = cells;
+        self.width = width;
+        self.height = height;
+        self.set_cursor(self.cursor.0, self.cursor.1);
+    }
+
+    /// erase cells of a line, with the current background
+    fn erase(&mut self, y: u16, x_start: u16, x_end: u16) {
+        let mut style = CompoundStyle::default();
+        style.object_style.background_color = self.style.get_bg();
+        for x in x_start..x_end.min(self.width) {
+            self.set(x, y, ' ', &style);
+        }
+    }
+    fn erase_lines(&mut self, y_start: u16, y_end: u16) {
+        for y in y_start..y_end.min(self.height) {
+            self.erase(y, 0, self.width);
+        }
+    }
+    /// move all lines up, as a terminal does when writing
+    /// after the last line
+    fn scroll_up(&mut self) {
+        let w = self.width as usize;
+        self.cells.drain(..w);
+        self.cells.extend(std::iter::repeat(Cell::default()).take(w));
+    }
+    fn line_feed(&mut self) {
+        if self.cursor.1 + 1 < self.height {
+            self.cursor.1 += 1;
+        } else {
+            self.scroll_up();
+        }
+    }
+    fn print(&mut self, ch: char) {
+        match ch {
+            '\n' => {
+                self.cursor.0 = 0;
+                self.line_feed();
+            }
+            '\r' => {
+                self.cursor.0 = 0;
+            }
+            '\t' => {
+                let x = (self.cursor.0 / 8 + 1) * 8;
+                self.cursor.0 = x.min(self.width);
+            }
+            _ => {
+                let w = ch.width().unwrap_or(0) as u16;
+                if w == 0 || self.width == 0 || self.height == 0 {
+                    return;
+                }
+                if self.cursor.0 + w > self.width {
+                    self.cursor.0 = 0;
+                    self.line_feed();
+                }
+                let style = self.style.clone();
+                self.set(self.cursor.0, self.cursor.1, ch, &style);
+                self.cursor.0 += w;
+            }
+        }
+    }
+    fn apply_csi(&mut self, params: &str, end: char) {
+        let nums: Vec<u16> = params
+            .split(';')
+            .map(|p| p.parse::<u16>().unwrap_or(0))
+            .collect();
+        let n = |i: usize| nums.get(i).copied().unwrap_or(0);
+        let count = n(0).max(1);
+        let (x, y) = self.cursor;
+        match end {
+            'H' | 'f' => self.set_cursor(n(1).max(1) - 1, n(0).max(1) - 1),
+            'G' => self.set_cursor(n(0).max(1) - 1, y),
+            'd' => self.set_cursor(x, n(0).max(1) - 1),
+            'A' => self.set_cursor(x, y.saturating_sub(count)),
+            'B' => self.set_cursor(x, y.saturating_add(count)),
+            'C' => self.set_cursor(x.saturating_add(count), y),
+            'D' => self.set_cursor(x.saturating_sub(count), y),
+            'E' => self.set_cursor(0, y.saturating_add(count)),
+            'F' => self.set_cursor(0, y.saturating_sub(count)),
+            'K' => match n(0) {
+                0 => self.erase(y, x, self.width),
+                1 => self.erase(y, 0, x + 1),
+                _ => self.erase(y, 0, self.width),
+            },
+            'J' => match n(0) {
+                0 => {
+                    self.erase(y, x, self.width);
+                    self.erase_lines(y + 1, self.height);
+                }
+                1 => {
+                    self.erase_lines(0, y);
+                    self.erase(y, 0, x + 1);
+                }
+                _ => self.erase_lines(0, self.height),
+            },
+            'm' => ansi::apply_sgr(&mut self.style, params),
+            'h' if params == "?25" => self.cursor_visible = true,
+            'l' if params == "?25" => self.cursor_visible = false,
+            _ => {} // not supported, or not affecting the grid (eg scrolling regions)
+        }
+    }
+    fn apply(&mut self, action: Action<'_>) {
+        match action {
+            Action::Print(ch) => self.print(ch),
+            Action::Csi { params, end } => self.apply_csi(params, end),
+            Action::SaveCursor => self.saved_cursor = self.cursor,
+            Action::RestoreCursor => {
+                let (x, y) = self.saved_cursor;
+                self.set_cursor(x, y);
+            }
+            Action::Osc(_) | Action::Ignore => {}
+        }
+    }
+}
+
+impl io::Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let pending = std::mem::take(&mut self.pending);
+        let (valid, rest) = match std::str::from_utf8(&pending) {
+            Ok(s) => (s, &[][..]),
+            Err(e) => {
+                let (valid, rest) = pending.split_at(e.valid_up_to());
+                // an invalid char is skipped, an incomplete one is kept for later
+                let rest = match e.error_len() {
+                    Some(len) => &rest[len..],
+                    None => rest,
+                };
+                (std::str::from_utf8(valid).unwrap_or_default(), rest)
+            }
+        };
+        let mut idx = 0;
+        while idx < valid.len() {
+            match ansi::parse_action(&valid[idx..]) {
+                Some((action, len)) => {
+                    self.apply(action);
+                    idx += len;
+                }
+                None => break, // incomplete sequence
+            }
+        }
+        let mut new_pending = valid.as_bytes()[idx..].to_vec();
+        new_pending.extend_from_slice(rest);
+        self.pending = new_pending;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod buffer_tests {
+    use {
+        super::*,
+        crate::crossterm::{
+            cursor::MoveTo,
+            queue,
+            style::{
+                Attribute,
+                Color,
+                Print,
+            },
+            terminal::{
+                Clear,
+                ClearType,
+            },
+        },
+        std::io::Write,
+    };
+
+    fn row_string(buffer: &Buffer, y: u16) -> String {
+        buffer
+            .row(y)
+            .iter()
+            .filter(|c| !c.is_wide_char_tail())
+            .map(|c| c.ch)
+            .collect()
+    }
+
+    #[test]
+    fn test_write_styled_content() {
+        let mut buffer = Buffer::new(10, 3);
+        let mut style = CompoundStyle::with_fgbg(Color::Yellow, Color::AnsiValue(235));
+        style.add_attr(Attribute::Bold);
+        queue!(buffer, MoveTo(2, 1), Print(style.apply_to("ab"))).unwrap();
+        write!(buffer, "c").unwrap();
+        assert_eq!(row_string(&buffer, 1), "  abc     ");
+        assert_eq!(buffer.cell(2, 1).unwrap().style, style);
+        assert_eq!(buffer.cell(4, 1).unwrap().style, CompoundStyle::default());
+        assert_eq!(buffer.cursor(), (5, 1));
+    }
+
+    #[test]
+    fn test_split_writes_and_wide_chars() {
+        let mut buffer = Buffer::new(6, 2);
+        let bytes = "\u{1b}[2;1H日本\u{1b}[1;3Hx".as_bytes();
+        for chunk in bytes.chunks(3) {
+            buffer.write_all(chunk).unwrap();
+        }
+        assert_eq!(row_string(&buffer, 0), "  x   ");
+        assert_eq!(row_string(&buffer, 1), "日本  ");
+        // overwriting the tail of a wide char clears its head
+        queue!(buffer, MoveTo(1, 1), Print('-')).unwrap();
+        assert_eq!(row_string(&buffer, 1), " -本  ");
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut buffer = Buffer::new(4, 2);
+        write!(buffer, "abcdefgh").unwrap();
+        assert_eq!(row_string(&buffer, 0), "abcd");
+        assert_eq!(row_string(&buffer, 1), "efgh");
+        let bg = CompoundStyle::with_bg(Color::Blue);
+        queue!(buffer, MoveTo(1, 0)).unwrap();
+        bg.clear(&mut buffer, ClearType::UntilNewLine).unwrap();
+        assert_eq!(row_string(&buffer, 0), "a   ");
+        assert_eq!(buffer.cell(3, 0).unwrap().style, bg);
+        queue!(buffer, Clear(ClearType::All)).unwrap();
+        assert_eq!(row_string(&buffer, 1), "    ");
+    }
+}
@@ -0,0 +1,43 @@
+use {
+    crate::CompoundStyle,
+    unicode_width::UnicodeWidthChar,
+};
+
+/// The char of the cell right of a wide char, which
+/// is covered by it
+pub(crate) const WIDE_CHAR_TAIL: char = '\0';
+
+/// A styled char in a [crate::Buffer]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: CompoundStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: CompoundStyle::default(),
+        }
+    }
+}
+
+impl Cell {
+    pub fn new(ch: char, style: CompoundStyle) -> Self {
+        Self { ch, style }
+    }
+    /// Tell whether the cell is covered by the wide char
+    /// of the cell at its left
+    pub fn is_wide_char_tail(&self) -> bool {
+        self.ch == WIDE_CHAR_TAIL
+    }
+    /// Return the number of columns taken by the char
+    pub fn width(&self) -> usize {
+        if self.is_wide_char_tail() {
+            0
+        } else {
+            self.ch.width().unwrap_or(0)
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! A grid of styled cells, to compose a whole screen before
+//! writing only what changed on the terminal.
+//!
+//! A [Buffer] implements `Write`, interpreting the escape sequences
+//! termimad emits, so that all views can draw into it with their
+//! usual `write_on` functions.
+//! A [Screen] holds the buffer of the previous frame, and queues
+//! only the cells which changed since.
+//!
+//! A buffer can also be used as a headless terminal, to test
+//! the rendering of views with plain or styled snapshots, or
+//! to make SVG images of them.
+pub(crate) mod ansi;
+mod buffer;
+mod cell;
+mod screen;
+mod snapshot;
+mod svg;
+
+pub use {
+    buffer::Buffer,
+    cell::Cell,
+    screen::Screen,
+    svg::SvgOptions,
+};
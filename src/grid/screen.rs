@@ -0,0 +1,218 @@
+use {
+    super::Buffer,
+    crate::{
+        crossterm::{
+            cursor::MoveTo,
+            queue,
+            style::{
+                Attribute,
+                Print,
+                SetAttribute,
+                SetAttributes,
+                SetUnderlineColor,
+            },
+        },
+        errors::Result,
+        terminal_size,
+        CompoundStyle,
+    },
+    std::io::{
+        self,
+        Write,
+    },
+};
+
+/// A double buffered screen.
+///
+/// Views are drawn into the [Buffer] returned by [Screen::buffer],
+/// then [Screen::flush_on] writes only the cells which changed since
+/// the previous flush, which prevents flickering and saves bandwidth
+/// (most notably over SSH):
+///
+/// ```
+/// use termimad::*;
+///
+/// let skin = MadSkin::default();
+/// let area = Area::new(0, 0, 30, 4);
+/// let mut screen = Screen::new(30, 4);
+/// let mut w = Vec::new();
+/// for s in ["Hello **world**", "Hello *there*"] {
+///     let text = skin.area_text(s, &area);
+///     TextView::from(&area, &text).write_on(screen.buffer()).unwrap();
+///     screen.flush_on(&mut w).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Screen {
+    /// what's on the terminal
+    front: Buffer,
+    /// what's being drawn
+    back: Buffer,
+    /// whether the terminal content is unknown, and must be
+    /// fully written on next flush
+    invalidated: bool,
+}
+
+impl Screen {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            front: Buffer::new(width, height),
+            back: Buffer::new(width, height),
+            invalidated: true,
+        }
+    }
+    /// Create a screen with the size of the terminal
+    pub fn for_terminal() -> Self {
+        let (width, height) = terminal_size();
+        Self::new(width, height)
+    }
+    pub fn width(&self) -> u16 {
+        self.back.width()
+    }
+    pub fn height(&self) -> u16 {
+        self.back.height()
+    }
+    /// Return the buffer into which the next frame is drawn.
+    ///
+    /// It initially contains the previous frame.
+    pub fn buffer(&mut self) -> &mut Buffer {
+        &mut self.back
+    }
+    /// Change the size of the screen, for example on a terminal
+    /// resize event. The next flush will write all cells.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.front = Buffer::new(width, height);
+        self.back.resize(width, height);
+        self.invalidated = true;
+    }
+    /// Ensure the next flush writes all cells, for example
+    /// because the terminal was cleared by another program
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+    /// Queue the changes between the previous frame and the buffer,
+    /// without flushing
+    pub fn queue_diff_on<W: Write>(&mut self, w: &mut W) -> Result<()> {
+        let mut cursor: Option<(u16, u16)> = None;
+        let mut style: Option<CompoundStyle> = None;
+        for y in 0..self.back.height() {
+            let back_row = self.back.row(y);
+            let front_row = self.front.row(y);
+            for (x, cell) in back_row.iter().enumerate() {
+                if cell.is_wide_char_tail() {
+                    continue;
+                }
+                let width = cell.width().max(1);
+                let changed = self.invalidated
+                    || back_row[x..x + width.min(back_row.len() - x)]
+                        != front_row[x..x + width.min(front_row.len() - x)];
+                if !changed {
+                    continue;
+                }
+                let x = x as u16;
+                if cursor != Some((x, y)) {
+                    queue!(w, MoveTo(x, y))?;
+                }
+                if style.as_ref() != Some(&cell.style) {
+                    // the named colors are written with their standard codes,
+                    // as in CompoundStyle::apply_to
+                    let object_style = &cell.style.object_style;
+                    queue!(w, SetAttribute(Attribute::Reset))?;
+                    cell.style.queue_bg(w)?;
+                    cell.style.queue_fg(w)?;
+                    if let Some(color) = object_style.underline_color {
+                        queue!(w, SetUnderlineColor(color))?;
+                    }
+                    if !object_style.attributes.is_empty() {
+                        queue!(w, SetAttributes(object_style.attributes))?;
+                    }
+                    style = Some(cell.style.clone());
+                }
+                queue!(w, Print(cell.ch))?;
+                cursor = Some((x + width as u16, y));
+            }
+        }
+        if style.is_some() {
+            queue!(w, SetAttribute(Attribute::Reset))?;
+        }
+        self.front.clone_from(&self.back);
+        self.invalidated = false;
+        Ok(())
+    }
+    /// Write the changes between the previous frame and the buffer,
+    /// then flush
+    pub fn flush_on<W: Write>(&mut self, w: &mut W) -> Result<()> {
+        self.queue_diff_on(w)?;
+        w.flush()?;
+        Ok(())
+    }
+    /// Write the changes on stdout, then flush
+    pub fn flush(&mut self) -> Result<()> {
+        self.flush_on(&mut io::stdout())
+    }
+}
+
+#[cfg(test)]
+mod screen_tests {
+    use {
+        super::*,
+        crate::crossterm::style::Color,
+    };
+
+    fn flushed(screen: &mut Screen) -> String {
+        let mut w = Vec::new();
+        screen.flush_on(&mut w).unwrap();
+        String::from_utf8(w).unwrap()
+    }
+
+    #[test]
+    fn test_only_changes_are_written() {
+        let mut screen = Screen::new(8, 2);
+        let style = CompoundStyle::with_fg(Color::Red);
+        screen.buffer().put_str(0, 0, "abcdef", &style);
+        let first = flushed(&mut screen);
+        assert!(first.contains("abcdef"));
+        assert_eq!(flushed(&mut screen), "");
+        screen.buffer().put_str(2, 0, "cX", &style);
+        let second = flushed(&mut screen);
+        assert!(second.starts_with("\u{1b}[1;4H"));
+        assert!(second.contains('X'));
+        assert!(!second.contains('c'));
+        screen.invalidate();
+        assert!(flushed(&mut screen).contains("abcXef"));
+    }
+
+    #[test]
+    fn test_named_colors_are_written_with_their_codes() {
+        let mut screen = Screen::new(4, 1);
+        let style = CompoundStyle::new(
+            Some(Color::Red),
+            Some(Color::DarkBlue),
+            Attribute::Bold.into(),
+        );
+        screen.buffer().put_str(0, 0, "ab", &style);
+        assert_eq!(
+            flushed(&mut screen),
+            concat!(
+                "\u{1b}[1;1H\u{1b}[0m\u{1b}[44m\u{1b}[91m\u{1b}[1mab",
+                "\u{1b}[0m  \u{1b}[0m",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_diff_replays_on_buffer() {
+        // writing the diffs in a buffer must give the same content
+        let mut screen = Screen::new(6, 3);
+        let mut terminal = Buffer::new(6, 3);
+        let styles = [
+            CompoundStyle::with_fg(Color::Red),
+            CompoundStyle::with_bg(Color::Blue),
+        ];
+        for (i, s) in ["a界b", "xyz", "日本"].iter().enumerate() {
+            screen.buffer().put_str(i as u16, i as u16 % 3, s, &styles[i % 2]);
+            screen.flush_on(&mut terminal).unwrap();
+            assert_eq!(terminal.cells(), screen.buffer().cells());
+        }
+    }
+}
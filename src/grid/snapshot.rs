@@ -0,0 +1,213 @@
+use {
+    super::Buffer,
+    crate::{
+        CompoundStyle,
+        PushStyleTokens,
+    },
+    std::fmt::Write,
+};
+
+/// chars used to mark the styles in a styled snapshot
+const STYLE_MARKS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Dumps of a buffer, mostly useful when the buffer is used as an
+/// headless terminal in tests
+impl Buffer {
+    /// Return the chars of the buffer, one line per row, without
+    /// the trailing spaces of the rows
+    pub fn to_plain_text(&self) -> String {
+        let mut s = String::new();
+        for y in 0..self.height() {
+            if y > 0 {
+                s.push('\n');
+            }
+            s.push_str(self.row_text(y).trim_end());
+        }
+        s
+    }
+
+    /// Return the chars of a row, wide chars counting once
+    pub fn row_text(&self, y: u16) -> String {
+        self.row(y)
+            .iter()
+            .filter(|cell| !cell.is_wide_char_tail())
+            .map(|cell| cell.ch)
+            .collect()
+    }
+
+    /// Return a textual snapshot of the buffer, with its styles.
+    ///
+    /// Each row is written between `|`, followed by a line where each
+    /// cell is marked with a letter referring to its style (or a space
+    /// for unstyled cells). The styles are listed at the end, in the
+    /// syntax of skin files:
+    ///
+    /// ```text
+    /// |Hello world|
+    /// |aaaaa bbbbb|
+    /// a: Bold
+    /// b: Yellow Italic
+    /// ```
+    pub fn to_styled_snapshot(&self) -> String {
+        let mut styles: Vec<&CompoundStyle> = Vec::new();
+        let mut s = String::new();
+        for y in 0..self.height() {
+            let _ = writeln!(s, "|{}|", self.row_text(y));
+            s.push('|');
+            for cell in self.row(y) {
+                if cell.style == CompoundStyle::default() {
+                    s.push(' ');
+                    continue;
+                }
+                let idx = match styles.iter().position(|&style| *style == cell.style) {
+                    Some(idx) => idx,
+                    None => {
+                        styles.push(&cell.style);
+                        styles.len() - 1
+                    }
+                };
+                s.push(STYLE_MARKS.chars().nth(idx).unwrap_or('?'));
+            }
+            s.push_str("|\n");
+        }
+        for (style, mark) in styles.iter().zip(STYLE_MARKS.chars()) {
+            let _ = writeln!(s, "{mark}: {}", style.to_style_tokens_string());
+        }
+        s
+    }
+}
+
+/// Tests of views rendered in a headless terminal
+#[cfg(test)]
+mod snapshot_tests {
+    use {
+        crate::{
+            crossterm::style::{
+                Attribute,
+                Color,
+            },
+            *,
+        },
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_styled_snapshot() {
+        let mut buffer = Buffer::new(8, 2);
+        let mut bold = CompoundStyle::with_fg(Color::Yellow);
+        bold.add_attr(Attribute::Bold);
+        buffer.put_str(0, 0, "Hi", &bold);
+        buffer.put_str(3, 0, "日本", &CompoundStyle::with_bg(gray(4)));
+        buffer.put_str(1, 1, "x", &bold);
+        assert_eq!(buffer.to_plain_text(), "Hi 日本\n x");
+        assert_eq!(
+            buffer.to_styled_snapshot(),
+            concat!(
+                "|Hi 日本 |\n",
+                "|aa bbbb |\n",
+                "| x      |\n",
+                "| a      |\n",
+                "a: Yellow Bold\n",
+                "b: none ansi(236)\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_text_view() {
+        let skin = MadSkin::no_style();
+        let area = Area::new(1, 0, 12, 3);
+        let text = skin.area_text("# Title\nsome *long* text to wrap", &area);
+        let mut view = TextView::from(&area, &text);
+        let mut buffer = Buffer::new(14, 3);
+        view.write_on(&mut buffer).unwrap();
+        assert_eq!(buffer.to_plain_text(), " Title      ▐\n some long  ▐\n text to    ▐");
+        view.line_down();
+        view.write_on(&mut buffer).unwrap();
+        assert_eq!(buffer.to_plain_text(), " some long  ▐\n text to    ▐\n wrap       ▐");
+    }
+
+    #[test]
+    fn test_rect() {
+        let mut buffer = Buffer::new(6, 4);
+        let mut rect = Rect::new(Area::new(1, 1, 4, 3), CompoundStyle::with_fg(Color::Blue));
+        rect.set_border_style(BORDER_STYLE_MIDDLE_ROUND_LINE);
+        rect.draw(&mut buffer).unwrap();
+        assert_eq!(
+            buffer.to_styled_snapshot(),
+            concat!(
+                "|      |\n",
+                "|      |\n",
+                "| ╭──╮ |\n",
+                "| aaaa |\n",
+                "| │  │ |\n",
+                "| a  a |\n",
+                "| ╰──╯ |\n",
+                "| aaaa |\n",
+                "a: Blue\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_input_field() {
+        let mut buffer = Buffer::new(8, 1);
+        let mut input = InputField::new(Area::new(0, 0, 8, 1));
+        input.set_normal_style(CompoundStyle::default());
+        input.set_str("hello");
+        let cursor = input.display_on(&mut buffer).unwrap();
+        assert_eq!(cursor, Some((5, 0)));
+        assert_eq!(buffer.to_plain_text(), "hello");
+        input.set_focus(false);
+        input.display_on(&mut buffer).unwrap();
+        assert_eq!(buffer.row_text(0), "hello   ");
+    }
+
+    #[test]
+    fn test_list_view() {
+        static STYLE: minimad::once_cell::sync::Lazy<CompoundStyle> =
+            minimad::once_cell::sync::Lazy::new(CompoundStyle::default);
+        let skin = MadSkin::no_style();
+        let columns = vec![
+            ListViewColumn::new(
+                "name",
+                6,
+                8,
+                Box::new(|(name, _): &(&str, u32)| ListViewCell::new(name.to_string(), &STYLE)),
+            )
+            .with_align(Alignment::Left),
+            ListViewColumn::new(
+                "size",
+                4,
+                6,
+                Box::new(|(_, size): &(&str, u32)| ListViewCell::new(size.to_string(), &STYLE)),
+            )
+            .with_align(Alignment::Right),
+        ];
+        let mut list_view = ListView::new(Area::new(0, 0, 16, 4), columns, &skin);
+        list_view.add_row(("apple", 12));
+        list_view.add_row(("banana", 7));
+        list_view.add_row(("cherry", 300));
+        list_view.update_dimensions();
+        list_view.select_first_line();
+        list_view.try_select_next(false);
+        let mut buffer = Buffer::new(16, 4);
+        list_view.write_on(&mut buffer).unwrap();
+        assert_eq!(
+            buffer.to_styled_snapshot(),
+            concat!(
+                "|  name  │ size  |\n",
+                "|                |\n",
+                "|────────┼────── |\n",
+                "|                |\n",
+                "|banana  │     7▐|\n",
+                "|aaaaaaaaaaaaaaab|\n",
+                "|cherry  │   300▐|\n",
+                "|               c|\n",
+                "a: none ansi(237)\n",
+                "b: ansi(237)\n",
+                "c: ansi(253)\n",
+            ),
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use {
+    super::Buffer,
+    crate::{
+        crossterm::style::{
+            Attribute,
+            Color,
+        },
+        css_color,
+        html::push_escaped,
+        Area,
+        CompoundStyle,
+        DisplayableLine,
+        FmtText,
+    },
+    std::{
+        fmt::Write,
+        io::Write as _,
+    },
+};
+
+/// Settings of the SVG rendering of a buffer or text
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    pub font_family: String,
+    /// font size, in pixels
+    pub font_size: u16,
+    /// width of a cell, in pixels
+    pub cell_width: u16,
+    /// height of a cell, in pixels
+    pub cell_height: u16,
+    /// color of the cells without background
+    pub background: Color,
+    /// color of the chars without foreground
+    pub foreground: Color,
+    /// empty space around the cells, in pixels
+    pub padding: u16,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            font_family: "Menlo, Consolas, 'DejaVu Sans Mono', monospace".to_string(),
+            font_size: 14,
+            cell_width: 9,
+            cell_height: 18,
+            background: Color::Rgb { r: 30, g: 30, b: 30 },
+            foreground: Color::Rgb { r: 210, g: 210, b: 210 },
+            padding: 8,
+        }
+    }
+}
+
+/// Return the colors (foreground, background) of a style, the
+/// reverse attribute being applied
+fn colors(style: &CompoundStyle, options: &SvgOptions) -> (String, Option<String>) {
+    let fg = style.get_fg().and_then(css_color);
+    let bg = style.get_bg().and_then(css_color);
+    let default_fg = || css_color(options.foreground).unwrap_or_default();
+    if style.has_attr(Attribute::Reverse) {
+        let default_bg = css_color(options.background).unwrap_or_default();
+        (bg.unwrap_or(default_bg), Some(fg.unwrap_or_else(default_fg)))
+    } else {
+        (fg.unwrap_or_else(default_fg), bg)
+    }
+}
+
+/// Return the attributes of a `<text>` element for a style
+fn text_attributes(style: &CompoundStyle, fg: &str) -> String {
+    let mut attributes = format!(" fill=\"{fg}\"");
+    if style.has_attr(Attribute::Bold) {
+        attributes.push_str(" font-weight=\"bold\"");
+    }
+    if style.has_attr(Attribute::Italic) {
+        attributes.push_str(" font-style=\"italic\"");
+    }
+    if style.has_attr(Attribute::Dim) {
+        attributes.push_str(" opacity=\"0.7\"");
+    }
+    let mut decorations = Vec::new();
+    let underlined = [
+        Attribute::Underlined,
+        Attribute::DoubleUnderlined,
+        Attribute::Undercurled,
+        Attribute::Underdotted,
+        Attribute::Underdashed,
+    ]
+    .iter()
+    .any(|&attr| style.has_attr(attr));
+    if underlined {
+        decorations.push("underline");
+    }
+    if style.has_attr(Attribute::CrossedOut) {
+        decorations.push("line-through");
+    }
+    if style.has_attr(Attribute::OverLined) {
+        decorations.push("overline");
+    }
+    if !decorations.is_empty() {
+        let _ = write!(attributes, " text-decoration=\"{}\"", decorations.join(" "));
+    }
+    attributes
+}
+
+/// SVG rendering, to make images of what would be displayed
+/// on a terminal
+impl Buffer {
+    /// Return an SVG image of the whole buffer
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        self.area_to_svg(&self.area(), options)
+    }
+
+    /// Return an SVG image of the cells of an area of the buffer
+    pub fn area_to_svg(&self, area: &Area, options: &SvgOptions) -> String {
+        let left = area.left.min(self.width());
+        let right = area.right().min(self.width());
+        let top = area.top.min(self.height());
+        let bottom = area.bottom().min(self.height());
+        let cw = options.cell_width as usize;
+        let ch = options.cell_height as usize;
+        let padding = options.padding as usize;
+        let width = (right - left) as usize * cw + 2 * padding;
+        let height = (bottom - top) as usize * ch + 2 * padding;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+            viewBox=\"0 0 {width} {height}\">",
+        );
+        let _ = writeln!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+            css_color(options.background).unwrap_or_default(),
+        );
+        svg.push_str("<g font-family=\"");
+        push_escaped(&mut svg, &options.font_family);
+        let _ = writeln!(svg, "\" font-size=\"{}\">", options.font_size);
+        for y in top..bottom {
+            let row = &self.row(y)[left as usize..right as usize];
+            let py = (y - top) as usize * ch + padding;
+            // backgrounds, one rect per run of cells with the same one
+            let mut x = 0;
+            while x < row.len() {
+                let (_, bg) = colors(&row[x].style, options);
+                let start = x;
+                while x < row.len() && colors(&row[x].style, options).1 == bg {
+                    x += 1;
+                }
+                if let Some(bg) = bg {
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{py}\" width=\"{}\" height=\"{ch}\" fill=\"{bg}\"/>",
+                        start * cw + padding,
+                        (x - start) * cw,
+                    );
+                }
+            }
+            // chars, one text per run of cells with the same style,
+            // stretched to the width of the cells to keep the alignment
+            let baseline = py + ch * 4 / 5;
+            let mut x = 0;
+            while x < row.len() {
+                let style = &row[x].style;
+                let start = x;
+                let mut text = String::new();
+                while x < row.len() && row[x].style == *style {
+                    if !row[x].is_wide_char_tail() {
+                        text.push(row[x].ch);
+                    }
+                    x += 1;
+                }
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let (fg, _) = colors(style, options);
+                let _ = write!(
+                    svg,
+                    "<text x=\"{}\" y=\"{baseline}\" textLength=\"{}\" \
+                    lengthAdjust=\"spacingAndGlyphs\" xml:space=\"preserve\"{}>",
+                    start * cw + padding,
+                    (x - start) * cw,
+                    text_attributes(style, &fg),
+                );
+                push_escaped(&mut svg, &text);
+                svg.push_str("</text>\n");
+            }
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+impl FmtText<'_, '_> {
+    /// Return an SVG image of the text as it would be displayed on
+    /// a terminal, at the width of the text (or at its content width
+    /// if it has no width)
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let width = self.width.unwrap_or_else(|| self.content_width());
+        let mut buffer = Buffer::new(width as u16, self.lines.len() as u16);
+        for (y, line) in self.lines.iter().enumerate() {
+            buffer.set_cursor(0, y as u16);
+            let line = DisplayableLine::new(self.skin, line, Some(width));
+            let _ = write!(buffer, "{line}");
+        }
+        buffer.to_svg(options)
+    }
+}
+
+#[cfg(test)]
+mod svg_tests {
+    use {
+        crate::{
+            crossterm::style::{
+                Attribute,
+                Color,
+            },
+            *,
+        },
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_buffer_svg() {
+        let mut buffer = Buffer::new(6, 2);
+        let mut style = CompoundStyle::with_fgbg(Color::Yellow, Color::DarkBlue);
+        style.add_attr(Attribute::Bold);
+        buffer.put_str(1, 0, "a<b", &style);
+        buffer.put_str(0, 1, "日本", &CompoundStyle::with_attr(Attribute::Underlined));
+        let options = SvgOptions {
+            font_family: "monospace".to_string(),
+            cell_width: 10,
+            cell_height: 20,
+            padding: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            buffer.to_svg(&options),
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"60\" height=\"40\" ",
+                "viewBox=\"0 0 60 40\">\n",
+                "<rect width=\"100%\" height=\"100%\" fill=\"#1e1e1e\"/>\n",
+                "<g font-family=\"monospace\" font-size=\"14\">\n",
+                "<rect x=\"10\" y=\"0\" width=\"30\" height=\"20\" fill=\"#000080\"/>\n",
+                "<text x=\"10\" y=\"16\" textLength=\"30\" lengthAdjust=\"spacingAndGlyphs\" ",
+                "xml:space=\"preserve\" fill=\"#ffff00\" font-weight=\"bold\">a&lt;b</text>\n",
+                "<text x=\"0\" y=\"36\" textLength=\"40\" lengthAdjust=\"spacingAndGlyphs\" ",
+                "xml:space=\"preserve\" fill=\"#d2d2d2\" text-decoration=\"underline\">",
+                "日本</text>\n",
+                "</g>\n</svg>\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_text_svg() {
+        let skin = MadSkin::no_style();
+        let text = FmtText::from(&skin, "# Title\nSome text to wrap", Some(10));
+        let svg = text.to_svg(&SvgOptions::default());
+        assert!(svg.contains(">Title     </text>"));
+        assert!(svg.contains(">Some text </text>"));
+        assert!(svg.contains(">to wrap   </text>"));
+    }
+}
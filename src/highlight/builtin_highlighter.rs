@@ -0,0 +1,19 @@
+use super::{
+    lang_def::LangDef,
+    CodeHighlighter,
+    CodeSpan,
+};
+
+/// A simple highlighter, supporting a few languages:
+/// rust, json, toml, and shell.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BuiltinHighlighter;
+
+/// The highlighter set by default in skins
+pub static BUILTIN_HIGHLIGHTER: BuiltinHighlighter = BuiltinHighlighter;
+
+impl CodeHighlighter for BuiltinHighlighter {
+    fn highlight(&self, lang: &str, lines: &[&str]) -> Option<Vec<Vec<CodeSpan>>> {
+        LangDef::by_name(lang).map(|def| def.lex_lines(lines))
+    }
+}
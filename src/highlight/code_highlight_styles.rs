@@ -0,0 +1,101 @@
+use {
+    super::CodeTokenKind,
+    crate::{
+        ansi,
+        gray,
+        ColorDepth,
+        CompoundStyle,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// The styles applied, over the code block style, to the
+/// tokens of highlighted code blocks.
+///
+/// Only the defined parts (for example the foreground color)
+/// are applied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodeHighlightStyles {
+    pub keyword: CompoundStyle,
+    #[serde(rename = "type")]
+    pub type_name: CompoundStyle,
+    pub constant: CompoundStyle,
+    pub string: CompoundStyle,
+    pub number: CompoundStyle,
+    pub comment: CompoundStyle,
+    pub key: CompoundStyle,
+    pub variable: CompoundStyle,
+    pub punctuation: CompoundStyle,
+}
+
+impl Default for CodeHighlightStyles {
+    fn default() -> Self {
+        Self {
+            keyword: CompoundStyle::with_fg(ansi(168)),
+            type_name: CompoundStyle::with_fg(ansi(74)),
+            constant: CompoundStyle::with_fg(ansi(173)),
+            string: CompoundStyle::with_fg(ansi(107)),
+            number: CompoundStyle::with_fg(ansi(173)),
+            comment: CompoundStyle::with_fg(gray(11)),
+            key: CompoundStyle::with_fg(ansi(110)),
+            variable: CompoundStyle::with_fg(ansi(179)),
+            punctuation: CompoundStyle::default(),
+        }
+    }
+}
+
+impl CodeHighlightStyles {
+    /// Build styles which don't change anything to the code block style
+    pub fn no_style() -> Self {
+        Self {
+            keyword: CompoundStyle::default(),
+            type_name: CompoundStyle::default(),
+            constant: CompoundStyle::default(),
+            string: CompoundStyle::default(),
+            number: CompoundStyle::default(),
+            comment: CompoundStyle::default(),
+            key: CompoundStyle::default(),
+            variable: CompoundStyle::default(),
+            punctuation: CompoundStyle::default(),
+        }
+    }
+    pub fn style(&self, kind: CodeTokenKind) -> &CompoundStyle {
+        match kind {
+            CodeTokenKind::Keyword => &self.keyword,
+            CodeTokenKind::Type => &self.type_name,
+            CodeTokenKind::Constant => &self.constant,
+            CodeTokenKind::String => &self.string,
+            CodeTokenKind::Number => &self.number,
+            CodeTokenKind::Comment => &self.comment,
+            CodeTokenKind::Key => &self.key,
+            CodeTokenKind::Variable => &self.variable,
+            CodeTokenKind::Punctuation => &self.punctuation,
+        }
+    }
+    pub fn blend_with<C: Into<coolor::Color> + Copy>(&mut self, color: C, weight: f32) {
+        self.keyword.blend_with(color, weight);
+        self.type_name.blend_with(color, weight);
+        self.constant.blend_with(color, weight);
+        self.string.blend_with(color, weight);
+        self.number.blend_with(color, weight);
+        self.comment.blend_with(color, weight);
+        self.key.blend_with(color, weight);
+        self.variable.blend_with(color, weight);
+        self.punctuation.blend_with(color, weight);
+    }
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        self.keyword.limit_to_color_depth(depth);
+        self.type_name.limit_to_color_depth(depth);
+        self.constant.limit_to_color_depth(depth);
+        self.string.limit_to_color_depth(depth);
+        self.number.limit_to_color_depth(depth);
+        self.comment.limit_to_color_depth(depth);
+        self.key.limit_to_color_depth(depth);
+        self.variable.limit_to_color_depth(depth);
+        self.punctuation.limit_to_color_depth(depth);
+    }
+}
@@ -0,0 +1,53 @@
+use {
+    super::CodeSpan,
+    std::fmt,
+};
+
+/// Something which can cut the lines of a code block into
+/// tokens of known kinds.
+///
+/// Termimad comes with a [crate::BuiltinHighlighter] covering a few
+/// common languages, but you may plug your own in the skin:
+///
+/// ```
+/// use termimad::*;
+///
+/// #[derive(Debug)]
+/// struct Shouter;
+/// impl CodeHighlighter for Shouter {
+///     fn highlight(&self, lang: &str, lines: &[&str]) -> Option<Vec<Vec<CodeSpan>>> {
+///         if lang != "shout" {
+///             return None;
+///         }
+///         Some(lines.iter().map(|line| vec![CodeSpan {
+///             start: 0,
+///             end: line.len(),
+///             kind: CodeTokenKind::Keyword,
+///         }]).collect())
+///     }
+/// }
+/// static SHOUTER: Shouter = Shouter;
+///
+/// let mut skin = MadSkin::default();
+/// skin.code_highlighter = Some(&SHOUTER);
+/// ```
+pub trait CodeHighlighter: fmt::Debug + Send + Sync {
+    /// Cut the lines of a code block into spans.
+    ///
+    /// Return `None` when the language isn't supported. Otherwise the
+    /// returned vector must have one entry per line, with ordered and
+    /// non overlapping spans whose bounds are byte indices in the line.
+    /// Parts of a line not covered by a span get the code block style.
+    fn highlight(&self, lang: &str, lines: &[&str]) -> Option<Vec<Vec<CodeSpan>>>;
+}
+
+/// Highlighters are compared by identity, so that skins can be
+/// compared
+impl PartialEq for dyn CodeHighlighter {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(
+            self as *const Self as *const u8,
+            other as *const Self as *const u8,
+        )
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    minimad::Compound,
+    CompoundRange,
+};
+
+/// The kind of a token recognized in a code line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeTokenKind {
+    Keyword,
+    Type,
+    /// Literal constants like `true`, `null`, `None`
+    Constant,
+    String,
+    Number,
+    Comment,
+    /// Keys of maps, in JSON or TOML for example
+    Key,
+    /// Shell variables like `$HOME`
+    Variable,
+    Punctuation,
+}
+
+/// A part of a code line, as returned by a [crate::CodeHighlighter]
+///
+/// `start` and `end` are byte indices in the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: CodeTokenKind,
+}
+
+/// A highlighted part of a code line, kept in the formatted composite.
+///
+/// The range follows the compounds resulting from the cutting
+/// of the line (for example when wrapping), which is how their
+/// kind is found at rendering.
+#[derive(Debug, Clone, Copy)]
+pub struct CodeToken {
+    pub range: CompoundRange,
+    pub kind: CodeTokenKind,
+}
+
+impl CodeToken {
+    /// Tell whether the compound of given index is part of this token
+    pub fn contains(&self, idx: usize, compound: &Compound<'_>) -> bool {
+        self.range.contains(idx, compound)
+    }
+}
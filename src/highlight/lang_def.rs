@@ -0,0 +1,410 @@
+use super::{
+    CodeSpan,
+    CodeTokenKind,
+};
+
+/// The definition of a language for the builtin lexer.
+///
+/// It's deliberately simple: the goal is to give some color to
+/// the snippets found in help screens, not to be exact.
+#[derive(Debug)]
+pub(crate) struct LangDef {
+    pub names: &'static [&'static str],
+    pub keywords: &'static [&'static str],
+    pub types: &'static [&'static str],
+    pub constants: &'static [&'static str],
+    pub line_comment: Option<&'static str>,
+    pub block_comment: Option<(&'static str, &'static str)>,
+    pub string_delimiters: &'static [char],
+    /// whether identifiers starting with an uppercase letter are types
+    pub capitalized_types: bool,
+    /// whether `$NAME` and `${...}` are variables
+    pub dollar_variables: bool,
+    /// whether a string or identifier followed by `:` or `=` is a key
+    pub keys: bool,
+    /// whether a line starting with `[` is a section header (TOML)
+    pub sections: bool,
+}
+
+pub(crate) static RUST: LangDef = LangDef {
+    names: &["rust", "rs"],
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "super", "trait", "type", "unsafe", "use",
+        "where", "while",
+    ],
+    types: &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+        "u16", "u32", "u64", "u128", "usize",
+    ],
+    constants: &["true", "false", "self", "None", "Some", "Ok", "Err"],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    string_delimiters: &['"'],
+    capitalized_types: true,
+    dollar_variables: false,
+    keys: false,
+    sections: false,
+};
+
+pub(crate) static JSON: LangDef = LangDef {
+    names: &["json", "jsonc", "json5", "hjson"],
+    keywords: &[],
+    types: &[],
+    constants: &["true", "false", "null"],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    string_delimiters: &['"'],
+    capitalized_types: false,
+    dollar_variables: false,
+    keys: true,
+    sections: false,
+};
+
+pub(crate) static TOML: LangDef = LangDef {
+    names: &["toml"],
+    keywords: &[],
+    types: &[],
+    constants: &["true", "false"],
+    line_comment: Some("#"),
+    block_comment: None,
+    string_delimiters: &['"', '\''],
+    capitalized_types: false,
+    dollar_variables: false,
+    keys: true,
+    sections: true,
+};
+
+pub(crate) static SHELL: LangDef = LangDef {
+    names: &["sh", "bash", "shell", "zsh", "console"],
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+    types: &[],
+    constants: &["true", "false"],
+    line_comment: Some("#"),
+    block_comment: None,
+    string_delimiters: &['"', '\''],
+    capitalized_types: false,
+    dollar_variables: true,
+    keys: false,
+    sections: false,
+};
+
+pub(crate) static LANG_DEFS: &[&LangDef] = &[&RUST, &JSON, &TOML, &SHELL];
+
+/// What's left open at the end of a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LexState {
+    Normal,
+    BlockComment,
+    String(char),
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl LangDef {
+    pub fn by_name(name: &str) -> Option<&'static LangDef> {
+        let name = name.to_lowercase();
+        LANG_DEFS
+            .iter()
+            .find(|def| def.names.contains(&name.as_str()))
+            .copied()
+    }
+
+    /// Cut the lines of a block into spans, keeping the state
+    /// (open comment or string) from one line to the next one
+    pub fn lex_lines(&self, lines: &[&str]) -> Vec<Vec<CodeSpan>> {
+        let mut state = LexState::Normal;
+        lines
+            .iter()
+            .map(|line| self.lex_line(line, &mut state))
+            .collect()
+    }
+
+    fn lex_line(&self, line: &str, state: &mut LexState) -> Vec<CodeSpan> {
+        let mut spans = Vec::new();
+        let mut idx = 0;
+        let mut push = |start: usize, end: usize, kind: CodeTokenKind| {
+            if end > start {
+                spans.push(CodeSpan { start, end, kind });
+            }
+        };
+        match *state {
+            LexState::BlockComment => {
+                let (_, end) = self.block_comment.unwrap_or(("", ""));
+                match line.find(end) {
+                    Some(pos) => {
+                        idx = pos + end.len();
+                        *state = LexState::Normal;
+                    }
+                    None => idx = line.len(),
+                }
+                push(0, idx, CodeTokenKind::Comment);
+            }
+            LexState::String(delimiter) => {
+                let (end, closed) = string_end(line, 0, delimiter);
+                if closed {
+                    *state = LexState::Normal;
+                }
+                idx = end;
+                push(0, idx, CodeTokenKind::String);
+            }
+            LexState::Normal => {}
+        }
+        let first_non_blank = line
+            .char_indices()
+            .find(|(_, c)| !c.is_whitespace())
+            .map_or(line.len(), |(i, _)| i);
+        if self.sections && idx == 0 && line[first_non_blank..].starts_with('[') {
+            let end = line.rfind(']').map_or(line.len(), |i| i + 1);
+            push(first_non_blank, end, CodeTokenKind::Type);
+            idx = end;
+        }
+        while idx < line.len() {
+            let rest = &line[idx..];
+            let c = rest.chars().next().unwrap();
+            if c.is_whitespace() {
+                idx += c.len_utf8();
+                continue;
+            }
+            if let Some(lc) = self.line_comment {
+                // in shell, a # inside a word isn't a comment
+                let at_word_start = idx == 0 || line[..idx].ends_with(char::is_whitespace);
+                if rest.starts_with(lc) && (!self.dollar_variables || at_word_start) {
+                    push(idx, line.len(), CodeTokenKind::Comment);
+                    break;
+                }
+            }
+            if let Some((start, end)) = self.block_comment {
+                if let Some(comment) = rest.strip_prefix(start) {
+                    let comment_end = match comment.find(end) {
+                        Some(pos) => idx + start.len() + pos + end.len(),
+                        None => {
+                            *state = LexState::BlockComment;
+                            line.len()
+                        }
+                    };
+                    push(idx, comment_end, CodeTokenKind::Comment);
+                    idx = comment_end;
+                    continue;
+                }
+            }
+            if self.string_delimiters.contains(&c) {
+                let (end, closed) = string_end(line, idx + 1, c);
+                if !closed {
+                    *state = LexState::String(c);
+                }
+                let kind = if self.keys && closed && is_followed_by_key_sign(&line[end..]) {
+                    CodeTokenKind::Key
+                } else {
+                    CodeTokenKind::String
+                };
+                push(idx, end, kind);
+                idx = end;
+                continue;
+            }
+            if c == '\'' && !self.string_delimiters.contains(&'\'') {
+                // probably a Rust char literal, or a lifetime
+                if let Some(end) = char_literal_end(rest) {
+                    push(idx, idx + end, CodeTokenKind::String);
+                    idx += end;
+                    continue;
+                }
+            }
+            if self.dollar_variables && c == '$' {
+                let end = if rest.starts_with("${") {
+                    rest.find('}').map_or(rest.len(), |i| i + 1)
+                } else {
+                    1 + rest[1..]
+                        .char_indices()
+                        .find(|&(_, c)| !is_ident_char(c))
+                        .map_or(rest.len() - 1, |(i, _)| i)
+                };
+                push(idx, idx + end, CodeTokenKind::Variable);
+                idx += end;
+                continue;
+            }
+            if c.is_ascii_digit() || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+            {
+                let end = 1 + rest[1..]
+                    .char_indices()
+                    .find(|&(_, c)| !(is_ident_char(c) || c == '.' || c == ':' || c == '-'))
+                    .map_or(rest.len() - 1, |(i, _)| i);
+                let kind = if idx > 0 && line[..idx].ends_with(is_ident_char) {
+                    // a digit in a word, not a number
+                    CodeTokenKind::Punctuation
+                } else {
+                    CodeTokenKind::Number
+                };
+                if kind == CodeTokenKind::Number {
+                    push(idx, idx + end, kind);
+                }
+                idx += end;
+                continue;
+            }
+            if is_ident_char(c) {
+                let end = rest
+                    .char_indices()
+                    .find(|&(_, c)| !(is_ident_char(c) || (self.keys && c == '-')))
+                    .map_or(rest.len(), |(i, _)| i);
+                let word = &rest[..end];
+                let kind = if self.keys && is_followed_by_key_sign(&rest[end..]) {
+                    Some(CodeTokenKind::Key)
+                } else if self.keywords.contains(&word) {
+                    Some(CodeTokenKind::Keyword)
+                } else if self.constants.contains(&word) {
+                    Some(CodeTokenKind::Constant)
+                } else if self.types.contains(&word)
+                    || (self.capitalized_types && c.is_uppercase())
+                {
+                    Some(CodeTokenKind::Type)
+                } else {
+                    None
+                };
+                if let Some(kind) = kind {
+                    push(idx, idx + end, kind);
+                }
+                idx += end;
+                continue;
+            }
+            if c.is_ascii_punctuation() {
+                push(idx, idx + 1, CodeTokenKind::Punctuation);
+            }
+            idx += c.len_utf8();
+        }
+        spans
+    }
+}
+
+/// Return the end of the string starting at `start` (after the opening delimiter),
+/// and whether the string is closed in this line
+fn string_end(line: &str, start: usize, delimiter: char) -> (usize, bool) {
+    let mut escaped = false;
+    for (i, c) in line[start..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && delimiter != '\'' {
+            escaped = true;
+        } else if c == delimiter {
+            return (start + i + c.len_utf8(), true);
+        }
+    }
+    (line.len(), false)
+}
+
+fn char_literal_end(s: &str) -> Option<usize> {
+    let mut chars = s.char_indices().skip(1);
+    match chars.next()? {
+        (_, '\\') => {
+            let (i, _) = chars.find(|&(_, c)| c == '\'')?;
+            Some(i + 1)
+        }
+        (_, _) => match chars.next()? {
+            (i, '\'') => Some(i + 1),
+            _ => None,
+        },
+    }
+}
+
+fn is_followed_by_key_sign(s: &str) -> bool {
+    let s = s.trim_start();
+    s.starts_with(':') || (s.starts_with('=') && !s.starts_with("=="))
+}
+
+#[cfg(test)]
+mod lexer_tests {
+    use super::*;
+
+    /// lex a line and return the tokens as (str, kind), for easier checking
+    fn lex<'s>(lang: &str, line: &'s str) -> Vec<(&'s str, CodeTokenKind)> {
+        let def = LangDef::by_name(lang).unwrap();
+        def.lex_lines(&[line])
+            .remove(0)
+            .into_iter()
+            .filter(|span| span.kind != CodeTokenKind::Punctuation)
+            .map(|span| (&line[span.start..span.end], span.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_line() {
+        use CodeTokenKind::*;
+        assert_eq!(
+            lex("rust", r#"let s: Option<String> = Some("a \"b\""); // c"#),
+            vec![
+                ("let", Keyword),
+                ("Option", Type),
+                ("String", Type),
+                ("Some", Constant),
+                (r#""a \"b\"""#, String),
+                ("// c", Comment),
+            ],
+        );
+        assert_eq!(
+            lex("rs", "fn f<'a>(c: char) -> u8 { 'x' as u8 + 42 }"),
+            vec![
+                ("fn", Keyword),
+                ("char", Type),
+                ("u8", Type),
+                ("'x'", String),
+                ("as", Keyword),
+                ("u8", Type),
+                ("42", Number),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_multiline_block_comment() {
+        let def = LangDef::by_name("rust").unwrap();
+        let lines = ["let a = 1; /* start", "still comment", "end */ a"];
+        let spans = def.lex_lines(&lines);
+        assert_eq!(spans[1].len(), 1);
+        assert_eq!(spans[1][0].kind, CodeTokenKind::Comment);
+        assert_eq!(spans[2][0].kind, CodeTokenKind::Comment);
+        assert_eq!(&lines[2][spans[2][0].start..spans[2][0].end], "end */");
+    }
+
+    #[test]
+    fn test_json_and_toml_keys() {
+        use CodeTokenKind::*;
+        assert_eq!(
+            lex("json", r#"{ "name": "termimad", "stars": 1000, "ok": true }"#),
+            vec![
+                (r#""name""#, Key),
+                (r#""termimad""#, String),
+                (r#""stars""#, Key),
+                ("1000", Number),
+                (r#""ok""#, Key),
+                ("true", Constant),
+            ],
+        );
+        assert_eq!(lex("toml", "[dependencies]"), vec![("[dependencies]", Type)]);
+        assert_eq!(
+            lex("TOML", r#"lazy-regex = "3.4" # fast"#),
+            vec![("lazy-regex", Key), (r#""3.4""#, String), ("# fast", Comment)],
+        );
+    }
+
+    #[test]
+    fn test_shell_line() {
+        use CodeTokenKind::*;
+        assert_eq!(
+            lex("bash", r#"if [ -n "$HOME" ]; then echo ${PATH}#x $USER; fi # done"#),
+            vec![
+                ("if", Keyword),
+                (r#""$HOME""#, String),
+                ("then", Keyword),
+                ("${PATH}", Variable),
+                ("$USER", Variable),
+                ("fi", Keyword),
+                ("# done", Comment),
+            ],
+        );
+    }
+}
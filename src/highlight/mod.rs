@@ -0,0 +1,18 @@
+//! Syntax highlighting of fenced code blocks.
+//!
+//! When a code block is introduced by a fence with a language
+//! (eg "```rust"), the skin's highlighter is asked to cut the
+//! lines into tokens, which are then rendered with the styles
+//! of the skin's `code_highlights`.
+mod builtin_highlighter;
+mod code_highlight_styles;
+mod code_highlighter;
+mod code_token;
+mod lang_def;
+
+pub use {
+    builtin_highlighter::*,
+    code_highlight_styles::*,
+    code_highlighter::*,
+    code_token::*,
+};
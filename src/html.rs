@@ -0,0 +1,537 @@
+use {
+    crate::{
+        crossterm::style::{
+            Attribute,
+            Color,
+        },
+        code::{
+            self,
+            FmtCodeFrame,
+            FmtFramedCode,
+        },
+        link,
+        skin::LeadingPart,
+        *,
+    },
+    std::fmt::Write,
+    unicode_width::UnicodeWidthStr,
+};
+
+/// Return the CSS value of a color, None for the default
+/// color of the terminal
+pub fn css_color(color: Color) -> Option<String> {
+    if color == Color::Reset {
+        return None;
+    }
+    let coolor::Rgb { r, g, b } = coolor::Color::from(color).rgb();
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+impl CompoundStyle {
+    /// Return the style as inline CSS declarations, for example
+    /// `"color:#ffff00;font-weight:bold"`
+    pub fn to_css(&self) -> String {
+        let mut fg = self.get_fg().and_then(css_color);
+        let mut bg = self.get_bg().and_then(css_color);
+        let mut declarations = Vec::new();
+        if self.has_attr(Attribute::Reverse) {
+            if fg.is_none() && bg.is_none() {
+                declarations.push("filter:invert(1)".to_string());
+            }
+            std::mem::swap(&mut fg, &mut bg);
+        }
+        if let Some(fg) = fg {
+            declarations.push(format!("color:{fg}"));
+        }
+        if let Some(bg) = bg {
+            declarations.push(format!("background-color:{bg}"));
+        }
+        if self.has_attr(Attribute::Bold) {
+            declarations.push("font-weight:bold".to_string());
+        }
+        if self.has_attr(Attribute::Dim) {
+            declarations.push("opacity:0.7".to_string());
+        }
+        if self.has_attr(Attribute::Italic) {
+            declarations.push("font-style:italic".to_string());
+        }
+        if self.has_attr(Attribute::Hidden) {
+            declarations.push("visibility:hidden".to_string());
+        }
+        let mut lines = Vec::new();
+        let mut underline_style = None;
+        for (attr, style) in [
+            (Attribute::Underlined, "solid"),
+            (Attribute::DoubleUnderlined, "double"),
+            (Attribute::Undercurled, "wavy"),
+            (Attribute::Underdotted, "dotted"),
+            (Attribute::Underdashed, "dashed"),
+        ] {
+            if self.has_attr(attr) {
+                underline_style = Some(style);
+            }
+        }
+        if let Some(style) = underline_style {
+            lines.push("underline");
+            if style != "solid" {
+                declarations.push(format!("text-decoration-style:{style}"));
+            }
+        }
+        if self.has_attr(Attribute::CrossedOut) {
+            lines.push("line-through");
+        }
+        if self.has_attr(Attribute::OverLined) {
+            lines.push("overline");
+        }
+        if !lines.is_empty() {
+            declarations.push(format!("text-decoration-line:{}", lines.join(" ")));
+            let color = self.object_style.underline_color.and_then(css_color);
+            if let Some(color) = color {
+                declarations.push(format!("text-decoration-color:{color}"));
+            }
+        }
+        declarations.join(";")
+    }
+}
+
+/// Push the string, with the chars special in HTML (and SVG) escaped
+pub(crate) fn push_escaped(html: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            _ => html.push(c),
+        }
+    }
+}
+
+/// Tell whether the URL can be the `href` of a link in HTML coming
+/// from untrusted markdown: its scheme, if any, must be allowed
+/// (`javascript:` URLs, for example, aren't)
+fn is_safe_href(url: &str) -> bool {
+    const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto", "ftp", "ftps"];
+    if url.chars().any(char::is_control) {
+        return false;
+    }
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(&['/', '?', '#'][..]) => ALLOWED_SCHEMES
+            .iter()
+            .any(|allowed| scheme.eq_ignore_ascii_case(allowed)),
+        _ => true, // a relative URL
+    }
+}
+
+/// Builds the HTML of a formatted text, with a span per run
+/// of chars of the same style.
+///
+/// The lines are laid out as by [MadSkin::write_fmt_line].
+struct HtmlWriter<'k> {
+    skin: &'k MadSkin,
+    html: String,
+    /// the chars not yet written, all of the same style
+    run: String,
+    run_style: CompoundStyle,
+}
+
+impl<'k> HtmlWriter<'k> {
+    fn new(skin: &'k MadSkin) -> Self {
+        Self {
+            skin,
+            html: String::new(),
+            run: String::new(),
+            run_style: CompoundStyle::default(),
+        }
+    }
+    fn flush_run(&mut self) {
+        if self.run.is_empty() {
+            return;
+        }
+        let css = self.run_style.to_css();
+        if css.is_empty() {
+            push_escaped(&mut self.html, &self.run);
+        } else {
+            let _ = write!(self.html, "<span style=\"{css}\">");
+            push_escaped(&mut self.html, &self.run);
+            self.html.push_str("</span>");
+        }
+        self.run.clear();
+    }
+    fn push_str(&mut self, style: &CompoundStyle, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        if self.run_style != *style {
+            self.flush_run();
+            self.run_style = style.clone();
+        }
+        self.run.push_str(s);
+    }
+    fn push_repeated(&mut self, style: &CompoundStyle, c: char, count: usize) {
+        if count > 0 {
+            self.push_str(style, &c.to_string().repeat(count));
+        }
+    }
+    fn push_styled_char(&mut self, sc: &StyledChar) {
+        self.push_str(sc.compound_style(), &sc.nude_char().to_string());
+    }
+    fn push_spaces(&mut self, count: usize) {
+        let skin = self.skin;
+        self.push_repeated(&skin.paragraph.compound_style, ' ', count);
+    }
+    fn open_link(&mut self, url: &str) {
+        self.flush_run();
+        self.html.push_str("<a href=\"");
+        push_escaped(&mut self.html, url);
+        self.html.push_str("\">");
+    }
+    fn close_link(&mut self) {
+        self.flush_run();
+        self.html.push_str("</a>");
+    }
+    fn push_border(&mut self, c: char) {
+        let skin = self.skin;
+        self.push_str(&skin.table.compound_style, &c.to_string());
+    }
+    /// Write the compounds of a composite, like `MadSkin::write_fmt_compounds`
+    fn write_compounds(&mut self, ls: &LineStyle, fc: &FmtComposite<'_>) {
+        let skin = self.skin;
+        let mut open_link = None;
+        for (idx, c) in fc.compounds.iter().enumerate() {
+            let link = skin
+                .fmt_compound_hyperlink(fc, idx)
+                .filter(|url| is_safe_href(url));
+            if link != open_link {
+                if open_link.is_some() {
+                    self.close_link();
+                }
+                if let Some(url) = link {
+                    self.open_link(url);
+                }
+                open_link = link;
+            }
+            #[cfg(feature = "special-renders")]
+            if let Some(replacement) = skin.special_chars.get(c) {
+                self.push_styled_char(replacement);
+                continue;
+            }
+            let style = skin.fmt_compound_style(ls, fc, idx);
+            self.push_str(&style, c.as_str());
+            self.push_repeated(&style, ' ', fc.padding_after(idx));
+            if let Some(number) = fc.footnote_after(idx) {
+                if open_link.take().is_some() {
+                    self.close_link();
+                }
+                let style = skin.fmt_composite_style(ls, fc);
+                self.push_str(&style, &link::footnote_mark(number));
+            }
+        }
+        if fc.hyphenated {
+            let style = match fc.compounds.len() {
+                0 => ls.compound_style.clone(),
+                len => skin.fmt_compound_style(ls, fc, len - 1),
+            };
+            self.push_str(&style, "-");
+        }
+        if open_link.is_some() {
+            self.close_link();
+        }
+    }
+    /// Write a composite, like [MadSkin::write_fmt_composite] without
+    /// right completion
+    fn write_composite(
+        &mut self,
+        fc: &FmtComposite<'_>,
+        outer_width: Option<usize>,
+        with_margins: bool,
+    ) {
+        let skin = self.skin;
+        let ls = skin.line_style(fc.kind);
+        let (leading, rpi, _) = skin.fmt_composite_leading(fc, outer_width, with_margins);
+        for part in &leading {
+            match part {
+                LeadingPart::Spaces(style, count) => self.push_repeated(style, ' ', *count),
+                LeadingPart::Char(sc) => self.push_styled_char(sc),
+                LeadingPart::Text(style, text) => self.push_str(style, text),
+            }
+        }
+        self.write_compounds(ls, fc);
+        self.push_repeated(&ls.compound_style, ' ', rpi);
+    }
+    /// Write a line, like [MadSkin::write_fmt_line] without right completion
+    fn write_line(&mut self, line: &FmtLine<'_>, width: Option<usize>) {
+        let skin = self.skin;
+        let tbc = &skin.table_border_chars;
+        match line {
+            FmtLine::Normal(fc) => {
+                self.write_composite(fc, width, true);
+            }
+            FmtLine::TableRow(FmtTableRow { cells }) => {
+                let tbl_width = 1 + cells.iter().fold(0, |sum, cell| {
+                    if let Some(spacing) = cell.spacing {
+                        sum + spacing.width + 1
+                    } else {
+                        sum + cell.visible_length + 1
+                    }
+                });
+                let (lpo, _) = Spacing::optional_completions(skin.table.align, tbl_width, width);
+                self.push_spaces(lpo);
+                for cell in cells {
+                    self.push_border(tbc.vertical);
+                    self.write_composite(cell, None, false);
+                }
+                self.push_border(tbc.vertical);
+            }
+            FmtLine::TableRule(rule) => {
+                let tbl_width = 1 + rule.widths.iter().fold(0, |sum, w| sum + w + 1);
+                let (lpo, _) = Spacing::optional_completions(skin.table.align, tbl_width, width);
+                self.push_spaces(lpo);
+                let (left, junction, right) = match rule.position {
+                    RelativePosition::Top => {
+                        (tbc.top_left_corner, tbc.top_junction, tbc.top_right_corner)
+                    }
+                    RelativePosition::Other => (tbc.left_junction, tbc.cross, tbc.right_junction),
+                    RelativePosition::Bottom => (
+                        tbc.bottom_left_corner,
+                        tbc.bottom_junction,
+                        tbc.bottom_right_corner,
+                    ),
+                };
+                self.push_border(left);
+                for (idx, &width) in rule.widths.iter().enumerate() {
+                    if idx > 0 {
+                        self.push_border(junction);
+                    }
+                    self.push_repeated(&skin.table.compound_style, tbc.horizontal, width);
+                }
+                self.push_border(right);
+            }
+            FmtLine::HorizontalRule => {
+                if let Some(w) = width {
+                    let rule = &skin.horizontal_rule;
+                    self.push_repeated(rule.compound_style(), rule.nude_char(), w);
+                }
+            }
+            FmtLine::CodeFrame(frame) => {
+                self.write_code_frame(frame, width);
+            }
+            FmtLine::FramedCode(line) => {
+                self.write_framed_code(line, width);
+            }
+        }
+    }
+    /// Write a border of a code block, like `MadSkin::write_code_frame`
+    fn write_code_frame(&mut self, frame: &FmtCodeFrame<'_>, width: Option<usize>) {
+        let skin = self.skin;
+        let Some(bc) = skin.code_block_border_chars else {
+            return;
+        };
+        let (lpo, _) = skin.code_frame_completions(frame.visible_length(), width);
+        self.push_spaces(lpo);
+        let (left, junction, right) = match frame.position {
+            RelativePosition::Bottom => (
+                bc.bottom_left_corner,
+                bc.bottom_junction,
+                bc.bottom_right_corner,
+            ),
+            _ => (bc.top_left_corner, bc.top_junction, bc.top_right_corner),
+        };
+        let border = &skin.table.compound_style;
+        self.push_border(left);
+        if frame.gutter_width > 0 {
+            self.push_repeated(border, bc.horizontal, frame.gutter_width + 2);
+            self.push_border(junction);
+        }
+        let mut code_part = frame.width + 2;
+        if let RelativePosition::Top = frame.position {
+            if let Some(label) = code::top_border_label(frame.lang, frame.width) {
+                self.push_border(bc.horizontal);
+                self.push_str(border, &label);
+                code_part -= 1 + label.width();
+            }
+        }
+        self.push_repeated(border, bc.horizontal, code_part);
+        self.push_border(right);
+    }
+    /// Write a line of a code block, like `MadSkin::write_framed_code`
+    fn write_framed_code(&mut self, line: &FmtFramedCode<'_>, width: Option<usize>) {
+        let skin = self.skin;
+        let Some(bc) = skin.code_block_border_chars else {
+            self.write_composite(&line.composite, width, true);
+            return;
+        };
+        let (lpo, _) = skin.code_frame_completions(line.visible_length(), width);
+        self.push_spaces(lpo);
+        self.push_border(bc.vertical);
+        self.push_spaces(1);
+        if line.gutter_width > 0 {
+            let number = line.number.map_or_else(String::new, |n| n.to_string());
+            let number = format!("{:>w$}", number, w = line.gutter_width);
+            self.push_str(&skin.table.compound_style, &number);
+            self.push_spaces(1);
+            self.push_border(bc.vertical);
+            self.push_spaces(1);
+        }
+        self.write_composite(&line.composite, None, false);
+        self.push_spaces(1);
+        self.push_border(bc.vertical);
+    }
+}
+
+impl FmtText<'_, '_> {
+    /// Render the text as HTML: a `<pre>` element in which the styles
+    /// of the skin are written as inline CSS.
+    ///
+    /// The text is laid out as on the terminal, with the same wrapping,
+    /// table borders, etc., so it must be displayed with a monospace font.
+    ///
+    /// When the skin renders links as hyperlinks, they're written as
+    /// `<a>` elements, unless their URL has a scheme which isn't known
+    /// to be safe (like `javascript:`).
+    ///
+    /// ```
+    /// use termimad::*;
+    /// let mut skin = MadSkin::no_style();
+    /// skin.bold.set_fg(crossterm::style::Color::AnsiValue(178));
+    /// let text = FmtText::from(&skin, "Some **bold** text", None);
+    /// assert_eq!(
+    ///     text.to_html(),
+    ///     "<pre>Some <span style=\"color:#d7af00\">bold</span> text\n</pre>\n",
+    /// );
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut writer = HtmlWriter::new(self.skin);
+        writer.html.push_str("<pre>");
+        for line in &self.lines {
+            writer.write_line(line, self.width);
+            writer.flush_run();
+            writer.html.push('\n');
+        }
+        writer.html.push_str("</pre>\n");
+        writer.html
+    }
+}
+
+#[cfg(test)]
+mod html_tests {
+    use {
+        crate::{
+            crossterm::style::{
+                Attribute,
+                Color,
+            },
+            *,
+        },
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_css() {
+        let mut style = CompoundStyle::with_fgbg(Color::Red, rgb(30, 30, 40));
+        style.add_attr(Attribute::Italic);
+        style.add_attr(Attribute::Undercurled);
+        style.add_attr(Attribute::CrossedOut);
+        assert_eq!(
+            style.to_css(),
+            "color:#ff0000;background-color:#1e1e28;font-style:italic;\
+            text-decoration-style:wavy;text-decoration-line:underline line-through",
+        );
+    }
+
+    #[test]
+    fn test_html_table() {
+        let mut skin = MadSkin::no_style();
+        skin.link_rendering = LinkRendering::Hyperlink;
+        skin.table.set_fg(gray(12));
+        skin.italic.add_attr(Attribute::Italic);
+        let md = "|a|b|\n|-|-|\n|*<i>*|[x](https://x.org?a&b)|\n\nA paragraph to be wrapped";
+        let text = FmtText::from(&skin, md, Some(20));
+        assert_eq!(
+            text.to_html(),
+            concat!(
+                "<pre><span style=\"color:#808080\">│</span> a <span style=\"color:#808080\">│",
+                "</span> b <span style=\"color:#808080\">│</span>\n",
+                "<span style=\"color:#808080\">├───┼───┤</span>\n",
+                "<span style=\"color:#808080\">│</span><span style=\"font-style:italic\">",
+                "&lt;i&gt;</span><span style=\"color:#808080\">│</span>",
+                "<a href=\"https://x.org?a&amp;b\">x</a>  ",
+                "<span style=\"color:#808080\">│</span>\n",
+                "\n",
+                "A paragraph to be \n",
+                "wrapped\n",
+                "</pre>\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_html_links() {
+        let mut skin = MadSkin::no_style();
+        skin.link_rendering = LinkRendering::Hyperlink;
+        skin.link.add_attr(Attribute::Underlined);
+        skin.bold.add_attr(Attribute::Bold);
+        let md = "[a **link**](https://dystroy.org), [relative](doc/a.md)\n\
+            [trap](javascript:alert) [trap](JavaScript:alert) [trap](data:text/html,x)";
+        let text = FmtText::from(&skin, md, None);
+        let link = |s: &str| format!("<span style=\"text-decoration-line:underline\">{s}</span>");
+        assert_eq!(
+            text.to_html(),
+            format!(
+                concat!(
+                    "<pre><a href=\"https://dystroy.org\">{}{}</a>, ",
+                    "<a href=\"doc/a.md\">{}</a>\n{} {} {}\n</pre>\n",
+                ),
+                link("a "),
+                "<span style=\"font-weight:bold;text-decoration-line:underline\">link</span>",
+                link("relative"),
+                link("trap"),
+                link("trap"),
+                link("trap"),
+            ),
+        );
+    }
+
+    /// Check that the HTML has the same layout as the text
+    /// rendered for the terminal
+    #[test]
+    fn test_html_layout() {
+        let mut skin = MadSkin {
+            link_rendering: LinkRendering::Footnotes,
+            code_block_border_chars: Some(ROUNDED_TABLE_BORDER_CHARS),
+            code_block_line_numbers: true,
+            ..Default::default()
+        };
+        skin.paragraph.align = Alignment::Center;
+        let md = "# Title\n\
+            * an item with a [link](https://dystroy.org)\n\
+            > a quote\n\
+            1. first\n\
+            2. second\n\
+            ---\n\
+            ```rust\n\
+            let a = \"<b>\";\n\
+            ```\n\
+            |a|b|\n|-|-|\n|c|d|";
+        let text = FmtText::from(&skin, md, Some(30));
+        let html = text.to_html();
+        let html = html.strip_prefix("<pre>").unwrap();
+        let html = html.strip_suffix("</pre>\n").unwrap();
+        let mut visible = String::new();
+        let mut in_tag = false;
+        for c in html.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                _ if !in_tag => visible.push(c),
+                _ => {}
+            }
+        }
+        let visible = visible
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&amp;", "&");
+        assert_eq!(visible, strip_ansi(&text.to_string()));
+    }
+}
@@ -27,6 +27,7 @@ skin.strikeout = CompoundStyle::new(Some(Red), None, Bold.into());
 
 **Beware:**
 * you may define colors in full [`rgb`](fn.rgb.html) but this will limit compatibility with old terminals. It's recommended to stick to [Ansi colors](fn.ansi.html), [gray levels](fn.gray.html), or [Crossterm predefined values](https://docs.rs/crossterm/0.9.6/crossterm/enum.Color.html).
+* if your skin uses rgb colors, you may call [`MadSkin::limit_to_terminal_colors`] to have them converted to the nearest ones the terminal supports.
 * styles are composed. For example a word may very well be italic, bold and striked out. It might not be wise to have them differ only by their background color for example.
 
 # Display a simple inline snippet
@@ -103,45 +104,74 @@ The repository contains several other examples, which hopefully cover the whole
 
 */
 
+mod admonition;
 mod area;
 mod ask;
 mod code;
 mod color;
+mod color_depth;
 mod composite;
 mod composite_kind;
+mod compound_range;
 mod compound_style;
 mod displayable_line;
 mod errors;
 mod events;
 mod fit;
+mod grid;
+mod highlight;
+mod html;
 mod inline;
 mod line;
+mod line_break;
 mod line_style;
+mod link;
 mod list_indentation;
+mod list_numbering;
 mod macros;
+mod mark;
 mod parse;
 mod rect;
+mod render_mode;
 mod scrollbar_style;
+mod search;
 mod serde;
 mod skin;
 mod spacing;
+mod streaming_text;
+mod styled;
 mod styled_char;
 mod table_border_chars;
+mod task_list;
+mod terminal_background;
 mod tbl;
 mod text;
 mod tokens;
 mod views;
 
 pub use {
+    admonition::*,
     area::{
         compute_scrollbar,
         terminal_size,
         Area,
     },
     ask::*,
+    code::{
+        FmtCodeFrame,
+        FmtFramedCode,
+    },
     color::*,
-    composite::FmtComposite,
+    color_depth::*,
+    composite::{
+        FmtComposite,
+        WrapJoint,
+    },
     composite_kind::*,
+    compound_range::{
+        CompoundPos,
+        CompoundRange,
+    },
     compound_style::*,
     coolor,
     crokey::crossterm,
@@ -149,28 +179,55 @@ pub use {
     displayable_line::DisplayableLine,
     errors::Error,
     events::{
+        EscapeSequence,
+        EventRecording,
+        EventScript,
         EventSource,
         EventSourceOptions,
+        RecordedEvent,
+        ReplayTiming,
         TickBeamId,
         Ticker,
         TimedEvent,
     },
     fit::*,
+    grid::*,
+    highlight::*,
+    html::css_color,
     inline::FmtInline,
     line::FmtLine,
+    line_break::Hyphenator,
     line_style::LineStyle,
+    link::{
+        FmtLink,
+        LinkRendering,
+    },
     list_indentation::*,
+    list_numbering::ListNumbering,
+    mark::{
+        MarkKind,
+        TextMark,
+    },
     minimad::{
         self,
         Alignment,
     },
     parse::*,
     rect::*,
+    render_mode::*,
     scrollbar_style::ScrollBarStyle,
+    search::{
+        SearchMatch,
+        SearchPattern,
+        TextSearch,
+    },
     skin::MadSkin,
     spacing::Spacing,
+    streaming_text::StreamingText,
+    styled::Styled,
     styled_char::StyledChar,
     table_border_chars::*,
+    terminal_background::*,
     text::FmtText,
     tbl::*,
     views::{
@@ -179,7 +236,10 @@ pub use {
         ListViewCell,
         ListViewColumn,
         MadView,
+        Pos,
         ProgressBar,
+        SelectionMode,
+        TextSelection,
         TextView,
     },
 };
@@ -4,6 +4,10 @@ use minimad::{
 };
 
 use crate::{
+    code::{
+        FmtCodeFrame,
+        FmtFramedCode,
+    },
     composite::FmtComposite,
     skin::MadSkin,
     tbl::{
@@ -15,12 +19,16 @@ use crate::{
 
 /// A line in a text. This structure should normally not be
 /// used outside of the lib.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FmtLine<'s> {
     Normal(FmtComposite<'s>),
     TableRow(FmtTableRow<'s>),
     TableRule(FmtTableRule),
     HorizontalRule,
+    /// top or bottom border of a framed code block
+    CodeFrame(FmtCodeFrame<'s>),
+    /// line of a framed code block
+    FramedCode(FmtFramedCode<'s>),
 }
 
 impl<'s> FmtLine<'s> {
@@ -46,6 +54,8 @@ impl<'s> FmtLine<'s> {
             FmtLine::TableRow(row) => row.cells.iter().fold(0, |s, c| s + c.visible_length), // Is that right ? no spacing ?
             FmtLine::TableRule(rule) => 1 + rule.widths.iter().fold(0, |s, w| s + w + 1),
             FmtLine::HorizontalRule => 0, // No intrinsic width
+            FmtLine::CodeFrame(frame) => frame.visible_length(),
+            FmtLine::FramedCode(line) => line.visible_length(),
         }
     }
 }
@@ -0,0 +1,208 @@
+use {
+    crate::{
+        FmtComposite,
+        FmtLine,
+        MadSkin,
+    },
+    std::fmt,
+};
+
+/// The soft hyphen, invisible unless a line is broken there
+pub(crate) const SOFT_HYPHEN: char = '\u{AD}';
+
+/// chars a line can't start with: closing punctuation, small kana, etc.
+const NO_BREAK_BEFORE: &str = ")]}>.,;:!?%\
+    、。，．：；！？）］｝」』】〕〉》〙〗〟’”\
+    ー々〻ゝゞヽヾ・…‥\
+    ぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶ";
+
+/// chars a line can't end with: opening punctuation
+const NO_BREAK_AFTER: &str = "([{<‘“（［｛「『【〔〈《〘〖〝";
+
+/// Something which can tell where a word may be hyphenated, for
+/// example based on a language dictionary.
+///
+/// ```
+/// use termimad::*;
+///
+/// /// a naive hyphenator cutting after every vowel
+/// #[derive(Debug)]
+/// struct VowelCutter;
+/// impl Hyphenator for VowelCutter {
+///     fn hyphenation_points(&self, word: &str) -> Vec<usize> {
+///         word.char_indices()
+///             .filter(|(_, c)| "aeiou".contains(*c))
+///             .map(|(idx, c)| idx + c.len_utf8())
+///             .collect()
+///     }
+/// }
+/// static VOWEL_CUTTER: VowelCutter = VowelCutter;
+///
+/// let mut skin = MadSkin::no_style();
+/// skin.hyphenator = Some(&VOWEL_CUTTER);
+/// let text = FmtText::from(&skin, "Donaudampfschiff", Some(10));
+/// assert_eq!(text.to_string(), "Donauda-\nmpfschiff\n");
+/// ```
+pub trait Hyphenator: fmt::Debug + Send + Sync {
+    /// Return the byte indices in the word where a line may be broken,
+    /// a hyphen being then added at the end of the first line.
+    ///
+    /// The passed word is only made of alphabetic chars.
+    fn hyphenation_points(&self, word: &str) -> Vec<usize>;
+}
+
+/// Hyphenators are compared by identity, so that skins can be
+/// compared
+impl PartialEq for dyn Hyphenator {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(
+            self as *const Self as *const u8,
+            other as *const Self as *const u8,
+        )
+    }
+}
+
+/// Return the hyphenation points of all the words of the string,
+/// as byte indices in the string
+pub(crate) fn hyphenation_points(hyphenator: &dyn Hyphenator, s: &str) -> Vec<usize> {
+    let mut points = Vec::new();
+    for word in lazy_regex::regex!(r"\p{Alphabetic}+").find_iter(s) {
+        let word_str = word.as_str();
+        for point in hyphenator.hyphenation_points(word_str) {
+            if point > 0 && point < word_str.len() && word_str.is_char_boundary(point) {
+                points.push(word.start() + point);
+            }
+        }
+    }
+    points
+}
+
+/// Return the number of soft hyphens of the composite, outside of code
+pub(crate) fn count_soft_hyphens(fc: &FmtComposite<'_>) -> usize {
+    fc.compounds
+        .iter()
+        .filter(|c| !c.code)
+        .map(|c| c.src.matches(SOFT_HYPHEN).count())
+        .sum()
+}
+
+/// Remove the soft hyphens of a composite, which is either not wrapped
+/// or broken at none of them
+pub(crate) fn remove_soft_hyphens(fc: &mut FmtComposite<'_>, skin: &MadSkin) {
+    if count_soft_hyphens(fc) == 0 {
+        return;
+    }
+    let mut compounds = Vec::with_capacity(fc.compounds.len() + 1);
+    for compound in fc.sourced_compounds() {
+        if compound.compound.code {
+            compounds.push(compound);
+            continue;
+        }
+        let mut start = 0;
+        for (idx, _) in compound.compound.src.match_indices(SOFT_HYPHEN) {
+            if idx > start {
+                compounds.push(compound.sub(start, idx));
+            }
+            start = idx + SOFT_HYPHEN.len_utf8();
+        }
+        if start < compound.compound.src.len() {
+            compounds.push(compound.tail(start));
+        }
+    }
+    fc.set_sourced_compounds(compounds);
+    fc.recompute_width(skin);
+}
+
+/// Remove the soft hyphens of the lines and table cells, once wrapped
+/// (the hyphens of the broken words are already written)
+pub(crate) fn remove_all_soft_hyphens(lines: &mut [FmtLine<'_>], skin: &MadSkin) {
+    for line in lines {
+        match line {
+            FmtLine::Normal(fc) => remove_soft_hyphens(fc, skin),
+            FmtLine::TableRow(row) => {
+                for cell in &mut row.cells {
+                    remove_soft_hyphens(cell, skin);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tell whether the char is an ideograph, or a syllable of a script
+/// which, like the ideographs, can be broken between any two chars
+fn is_ideographic(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2E80..=0x2FFF // radicals
+        | 0x3040..=0x31FF // kana, bopomofo
+        | 0x3400..=0x4DBF // ideographs extension A
+        | 0x4E00..=0x9FFF // unified ideographs
+        | 0xA000..=0xA4CF // yi
+        | 0xAC00..=0xD7A3 // hangul syllables
+        | 0xF900..=0xFAFF // compatibility ideographs
+        | 0x20000..=0x3FFFD // ideographs extensions
+    )
+}
+
+/// Tell whether a line may be broken between the chars `a` and `b`,
+/// none of them being a space, following a simplified version of the
+/// Unicode line breaking algorithm (UAX #14):
+///  - between ideographs, and between an ideograph and another char,
+///    unless a punctuation forbids it
+///  - after a hyphen between letters (`before` is the char before `a`)
+///  - around an em dash
+pub(crate) fn is_break_opportunity(before: Option<char>, a: char, b: char) -> bool {
+    if NO_BREAK_BEFORE.contains(b) || NO_BREAK_AFTER.contains(a) {
+        return false;
+    }
+    if a == '-' {
+        return b.is_alphabetic() && before.map_or(false, char::is_alphanumeric);
+    }
+    if a == '—' || b == '—' {
+        return a != b;
+    }
+    is_ideographic(a) || is_ideographic(b)
+}
+
+#[cfg(test)]
+mod line_break_tests {
+    use {
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_cjk_breaking() {
+        let skin = MadSkin::no_style();
+        let md = "日本語の文章は、単語の間に空白がありません。";
+        let text = FmtText::from(&skin, md, Some(12)).to_string();
+        assert_eq!(text, "日本語の文章\nは、単語の間\nに空白があり\nません。\n");
+    }
+
+    #[test]
+    fn test_hyphens() {
+        let skin = MadSkin::no_style();
+        let md = "Die Donau\u{AD}dampf\u{AD}schiff\u{AD}fahrt, a well-known -word";
+        let text = FmtText::from(&skin, md, Some(14)).to_string();
+        assert_eq!(text, "Die Donau-\ndampfschiff-\nfahrt, a well-\nknown -word\n");
+        // the soft hyphens where the line isn't broken aren't written
+        for width in [Some(14), None] {
+            let text = FmtText::from(&skin, "Donau\u{AD}dampf", width);
+            assert_eq!(text.to_string(), "Donaudampf\n");
+            assert_eq!(text.content_width(), 10);
+        }
+        assert_eq!(skin.inline("Donau\u{AD}dampf").to_string(), "Donaudampf");
+    }
+
+    #[test]
+    fn test_hyphen_in_link() {
+        let mut skin = MadSkin::no_style();
+        skin.link_rendering = LinkRendering::Hyperlink;
+        let md = "[Donau\u{AD}dampf\u{AD}schiff](https://dystroy.org)";
+        let text = FmtText::from(&skin, md, Some(12)).to_string();
+        assert_eq!(strip_ansi(&text), "Donaudampf-\nschiff\n");
+        // the hyphen is written in the hyperlink
+        assert!(text.contains("Donaudampf-\u{1b}]8;;\u{1b}\\"));
+    }
+}
@@ -1,5 +1,6 @@
 use {
     crate::{
+        color_depth::ColorDepth,
         compound_style::CompoundStyle,
         crossterm::style::{
             Attribute,
@@ -15,12 +16,16 @@ use {
 /// It's made of
 ///  - the base style of the compounds
 ///  - the alignment
+///  - whether the wrapped lines are justified
 #[derive(Default, Clone, Debug, PartialEq)]
 pub struct LineStyle {
     pub compound_style: CompoundStyle,
     pub align: Alignment,
     pub left_margin: usize,
     pub right_margin: usize,
+    /// Whether the lines of a wrapped paragraph, apart from the last
+    /// one, are filled by widening the spaces between words
+    pub justified: bool,
 }
 
 impl LineStyle {
@@ -41,6 +46,7 @@ impl LineStyle {
             align,
             left_margin: 0,
             right_margin: 0,
+            justified: false,
         }
     }
 
@@ -88,6 +94,10 @@ impl LineStyle {
     pub fn blend_with<C: Into<coolor::Color>>(&mut self, color: C, weight: f32) {
         self.compound_style.blend_with(color, weight);
     }
+
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        self.compound_style.limit_to_color_depth(depth);
+    }
 }
 
 impl From<CompoundStyle> for LineStyle {
@@ -97,6 +107,7 @@ impl From<CompoundStyle> for LineStyle {
             align: Alignment::Unspecified,
             left_margin: 0,
             right_margin: 0,
+            justified: false,
         }
     }
 }
@@ -0,0 +1,366 @@
+use {
+    crate::{
+        compound_range::SourcedCompound,
+        minimad::Compound,
+        CompositeKind,
+        CompoundPos,
+        CompoundRange,
+        FmtComposite,
+        FmtLine,
+        MadSkin,
+    },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+    std::fmt,
+};
+
+/// How markdown links (`[text](url)`) are rendered.
+///
+/// Unless they're written as in the markdown source, the text of
+/// the link gets the `link` style of the skin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkRendering {
+    /// The link is written as in the markdown source, brackets
+    /// and URL included
+    #[default]
+    Markdown,
+    /// Only the text of the link is written
+    Text,
+    /// The text is wrapped in an OSC 8 escape sequence, which
+    /// makes it clickable in most modern terminals
+    Hyperlink,
+    /// The URL is written after the text, in parentheses
+    UrlInParentheses,
+    /// A number is written after the text and the URLs are
+    /// listed at the end of the text
+    Footnotes,
+}
+
+/// A link found in a composite.
+///
+/// The text range follows the compounds when they're cut (for
+/// example when wrapping).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FmtLink<'s> {
+    pub text: CompoundRange,
+    pub url: &'s str,
+    /// number of the footnote listing the URL, when links are
+    /// rendered as footnotes: its mark is written after the text
+    pub footnote: Option<usize>,
+}
+
+impl<'s> FmtLink<'s> {
+    pub const fn new(text: CompoundRange, url: &'s str) -> Self {
+        Self {
+            text,
+            url,
+            footnote: None,
+        }
+    }
+    /// Tell whether the compound of given index is part of the text of the link
+    pub fn contains(&self, idx: usize, compound: &Compound<'_>) -> bool {
+        self.text.contains(idx, compound)
+    }
+    /// Tell whether the compound of given index ends the text of the link
+    pub fn ends_with(&self, idx: usize, compound: &Compound<'_>) -> bool {
+        self.text.ends_with(idx, compound)
+    }
+    /// Tell whether the URL can be safely written in an escape sequence
+    pub fn is_url_safe(&self) -> bool {
+        !self.url.chars().any(char::is_control)
+    }
+}
+
+/// Write the OSC 8 sequence opening a hyperlink
+pub(crate) fn write_hyperlink_start(f: &mut fmt::Formatter<'_>, url: &str) -> fmt::Result {
+    write!(f, "\u{1b}]8;;{url}\u{1b}\\")
+}
+
+/// Write the OSC 8 sequence closing a hyperlink
+pub(crate) fn write_hyperlink_end(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\u{1b}]8;;\u{1b}\\")
+}
+
+/// Return the mark referencing the footnote of the given
+/// number, for example `"[3]"`
+pub(crate) fn footnote_mark(number: usize) -> String {
+    format!("[{number}]")
+}
+
+/// Return the width of the mark referencing the footnote
+/// of the given number
+pub(crate) fn footnote_mark_width(number: usize) -> usize {
+    let mut width = 3;
+    let mut n = number / 10;
+    while n > 0 {
+        width += 1;
+        n /= 10;
+    }
+    width
+}
+
+/// The position of a link in a slice of compounds
+#[derive(Debug)]
+struct LinkPos {
+    open: (usize, usize),  // compound index and byte index of '['
+    close: (usize, usize), // compound index and byte index of ']'
+    url_end: usize,        // byte index of ')' in the closing compound
+}
+
+/// return the length of the URL at the start of `s`, if it's
+/// followed by the closing parenthesis
+fn url_len(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return if i > 0 { Some(i) } else { None },
+            ')' => depth -= 1,
+            c if c.is_whitespace() => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_links(compounds: &[Compound<'_>]) -> Vec<LinkPos> {
+    let mut links = Vec::new();
+    let mut open = None;
+    for (ci, compound) in compounds.iter().enumerate() {
+        if compound.code {
+            continue;
+        }
+        let src = compound.src;
+        let mut i = 0;
+        while i < src.len() {
+            match src.as_bytes()[i] {
+                b'[' => {
+                    open = Some((ci, i));
+                }
+                b']' => {
+                    if let Some(open) = open.take() {
+                        let after = &src[i + 1..];
+                        let is_empty = open == (ci, i.wrapping_sub(1));
+                        if let (Some(len), false) = (
+                            after.strip_prefix('(').and_then(url_len),
+                            is_empty,
+                        ) {
+                            let url_end = i + 2 + len;
+                            links.push(LinkPos {
+                                open,
+                                close: (ci, i),
+                                url_end,
+                            });
+                            i = url_end;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+    links
+}
+
+/// Extract the markdown links of a composite.
+///
+/// The brackets and the URLs are removed from the compounds, unless
+/// the URLs must be displayed in parentheses.
+pub(crate) fn extract_links(fc: &mut FmtComposite<'_>, rendering: LinkRendering) {
+    if rendering == LinkRendering::Markdown {
+        return;
+    }
+    let positions = find_links(&fc.compounds);
+    if positions.is_empty() {
+        return;
+    }
+    // byte ranges to remove in each compound, with whether the removed
+    // part is the end of the link
+    let mut cuts: Vec<Vec<(usize, usize, bool)>> = vec![Vec::new(); fc.compounds.len()];
+    for pos in &positions {
+        let (oci, obi) = pos.open;
+        let (cci, cbi) = pos.close;
+        let text_end = if cbi == 0 {
+            // the text ends with the previous compound
+            CompoundPos::new(cci - 1, fc.compounds[cci - 1].src.len())
+        } else {
+            CompoundPos::new(cci, cbi)
+        };
+        let text = CompoundRange::new(CompoundPos::new(oci, obi + 1), text_end);
+        let url = &fc.compounds[cci].src[cbi + 2..pos.url_end];
+        fc.links.push(FmtLink::new(text, url));
+        cuts[oci].push((obi, obi + 1, false));
+        match rendering {
+            LinkRendering::UrlInParentheses => cuts[cci].push((cbi, cbi + 1, true)),
+            _ => cuts[cci].push((cbi, pos.url_end + 1, true)),
+        }
+    }
+    let mut compounds = Vec::with_capacity(fc.compounds.len() + 2 * positions.len());
+    for (compound, cuts) in fc.sourced_compounds().into_iter().zip(cuts) {
+        let mut idx = 0;
+        for (start, end, link_end) in cuts {
+            if start > idx {
+                compounds.push(compound.sub(idx, start));
+            }
+            if link_end && rendering == LinkRendering::UrlInParentheses {
+                compounds.push(SourcedCompound::inserted(Compound::raw_str(" ")));
+            }
+            idx = end;
+        }
+        if idx == 0 {
+            compounds.push(compound);
+        } else if idx < compound.compound.src.len() {
+            compounds.push(compound.tail(idx));
+        }
+    }
+    fc.set_sourced_compounds(compounds);
+}
+
+/// Number the footnotes of the links, whose marks are written
+/// after their text, and list the URLs at the end of the text
+pub(crate) fn add_footnotes<'s>(lines: &mut Vec<FmtLine<'s>>, skin: &MadSkin) {
+    let mut urls = Vec::new();
+    for line in lines.iter_mut() {
+        match line {
+            FmtLine::Normal(fc) => number_footnotes(fc, &mut urls, skin),
+            FmtLine::TableRow(row) => {
+                for cell in &mut row.cells {
+                    number_footnotes(cell, &mut urls, skin);
+                }
+            }
+            _ => {}
+        }
+    }
+    if urls.is_empty() {
+        return;
+    }
+    lines.push(FmtLine::Normal(FmtComposite::new()));
+    for (idx, url) in urls.into_iter().enumerate() {
+        let kind = CompositeKind::Footnote(idx + 1);
+        let url_compound = Compound::raw_str(url);
+        let mut fc = FmtComposite {
+            kind,
+            visible_length: skin.visible_composite_length(kind, &[]),
+            ..FmtComposite::new()
+        };
+        fc.links.push(FmtLink::new(CompoundRange::of_compound(0, &url_compound), url));
+        fc.add_compound(url_compound);
+        lines.push(FmtLine::Normal(fc));
+    }
+}
+
+fn number_footnotes<'s>(fc: &mut FmtComposite<'s>, urls: &mut Vec<&'s str>, skin: &MadSkin) {
+    if fc.kind == CompositeKind::Code || fc.links.is_empty() {
+        return;
+    }
+    for link in &mut fc.links {
+        let mut compounds = fc.compounds.iter().enumerate();
+        if compounds.any(|(idx, c)| link.ends_with(idx, c)) {
+            urls.push(link.url);
+            link.footnote = Some(urls.len());
+        }
+    }
+    fc.recompute_width(skin);
+}
+
+#[cfg(test)]
+mod link_tests {
+    use {
+        super::*,
+        crate::minimad::Composite,
+    };
+
+    /// return the compounds, the compounds in links, and the urls
+    fn extract(md: &str, rendering: LinkRendering) -> (Vec<&str>, Vec<&str>, Vec<&str>) {
+        let mut fc = FmtComposite::new();
+        fc.compounds = Composite::from_inline(md).compounds;
+        extract_links(&mut fc, rendering);
+        let in_links = fc
+            .compounds
+            .iter()
+            .enumerate()
+            .filter(|(idx, c)| fc.links.iter().any(|l| l.contains(*idx, c)))
+            .map(|(_, c)| c.src)
+            .collect();
+        let all = fc.compounds.iter().map(|c| c.src).collect();
+        (all, in_links, fc.links.iter().map(|l| l.url).collect())
+    }
+
+    #[test]
+    fn test_extract_links() {
+        assert_eq!(
+            extract("see [the doc](https://x.org/a_(b)) now", LinkRendering::Hyperlink),
+            (
+                vec!["see ", "the doc", " now"],
+                vec!["the doc"],
+                vec!["https://x.org/a_(b)"],
+            ),
+        );
+        assert_eq!(
+            extract("[**bold** link](u) [not a link] (v)", LinkRendering::Text),
+            (
+                vec!["bold", " link", " [not a link] (v)"],
+                vec!["bold", " link"],
+                vec!["u"],
+            ),
+        );
+        assert_eq!(
+            extract("a [b](c)", LinkRendering::UrlInParentheses),
+            (vec!["a ", "b", " ", "(c)"], vec!["b"], vec!["c"]),
+        );
+        assert_eq!(extract("`[b](c)` []()", LinkRendering::Hyperlink).2, Vec::<&str>::new());
+    }
+
+    /// render the markdown with a skin without style but links
+    /// rendered as requested
+    fn render(md: &str, rendering: LinkRendering, width: usize) -> String {
+        let mut skin = MadSkin::no_style();
+        skin.link_rendering = rendering;
+        crate::FmtText::from(&skin, md, Some(width)).to_string()
+    }
+
+    #[test]
+    fn test_link_renderings() {
+        let md = "see [the doc](https://x.org) now";
+        assert_eq!(render(md, LinkRendering::default(), 40), "see [the doc](https://x.org) now\n");
+        assert_eq!(render(md, LinkRendering::Text, 40), "see the doc now\n");
+        assert_eq!(
+            render(md, LinkRendering::Hyperlink, 40),
+            "see \u{1b}]8;;https://x.org\u{1b}\\the doc\u{1b}]8;;\u{1b}\\ now\n",
+        );
+        assert_eq!(
+            render(md, LinkRendering::UrlInParentheses, 40),
+            "see the doc (https://x.org) now\n",
+        );
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let md: String = (1..=10).map(|i| format!("[l{i}](u{i}) ")).collect();
+        assert_eq!(
+            // the marks are never separated from the text of their link
+            render(md.trim_end(), LinkRendering::Footnotes, 22),
+            concat!(
+                "l1[1] l2[2] l3[3] \n",
+                "l4[4] l5[5] l6[6] \n",
+                "l7[7] l8[8] l9[9] \n",
+                "l10[10]\n",
+                "\n",
+                "[1] u1\n",
+                "[2] u2\n",
+                "[3] u3\n",
+                "[4] u4\n",
+                "[5] u5\n",
+                "[6] u6\n",
+                "[7] u7\n",
+                "[8] u8\n",
+                "[9] u9\n",
+                "[10] u10\n",
+            ),
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use {
+    crate::{
+        line::FmtLine,
+        CompositeKind,
+        MadSkin,
+    },
+    lazy_regex::*,
+    minimad::Compound,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// How the items of an ordered list are numbered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ListNumbering {
+    /// 1, 2, 3...
+    #[default]
+    Decimal,
+    /// a, b, c... then aa, ab...
+    LowerAlpha,
+    /// A, B, C... then AA, AB...
+    UpperAlpha,
+    /// i, ii, iii, iv...
+    LowerRoman,
+    /// I, II, III, IV...
+    UpperRoman,
+}
+
+impl ListNumbering {
+    /// Return the representation of the number, without the dot
+    pub fn format(self, number: usize) -> String {
+        match self {
+            Self::Decimal => number.to_string(),
+            Self::LowerAlpha => alpha(number),
+            Self::UpperAlpha => alpha(number).to_uppercase(),
+            Self::LowerRoman => roman(number),
+            Self::UpperRoman => roman(number).to_uppercase(),
+        }
+    }
+}
+
+fn alpha(mut number: usize) -> String {
+    if number == 0 {
+        return "0".to_string();
+    }
+    let mut chars = Vec::new();
+    while number > 0 {
+        number -= 1;
+        chars.push((b'a' + (number % 26) as u8) as char);
+        number /= 26;
+    }
+    chars.iter().rev().collect()
+}
+
+fn roman(mut number: usize) -> String {
+    const SYMBOLS: &[(usize, &str)] = &[
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    if number == 0 || number >= 4000 {
+        return number.to_string();
+    }
+    let mut s = String::new();
+    for &(value, symbol) in SYMBOLS {
+        while number >= value {
+            s.push_str(symbol);
+            number -= value;
+        }
+    }
+    s
+}
+
+/// If the compounds start with an ordered list item mark (like `"2. "`
+/// or `" 3) "`), return the depth and number of the item and the length
+/// of the mark
+fn ordered_item_mark(compounds: &[Compound<'_>]) -> Option<(u8, usize, usize)> {
+    let first = compounds.first().filter(|c| !c.code)?;
+    let (mark, indent, number) = regex_captures!(r"^( {0,3})(\d{1,9})[.)] ", first.src)?;
+    let number = number.parse().ok()?;
+    Some((indent.len() as u8, number, mark.len()))
+}
+
+/// Tell whether the compounds of a paragraph make an item of an ordered list
+pub(crate) fn is_ordered_item(compounds: &[Compound<'_>]) -> bool {
+    ordered_item_mark(compounds).is_some()
+}
+
+/// If the compounds of a paragraph start with an ordered list item mark,
+/// remove it and return the kind of the item, with the number written
+/// in the source
+pub(crate) fn extract_ordered_item(compounds: &mut Vec<Compound<'_>>) -> Option<CompositeKind> {
+    let (depth, number, len) = ordered_item_mark(compounds)?;
+    compounds[0].src = &compounds[0].src[len..];
+    if compounds[0].src.is_empty() {
+        compounds.remove(0);
+    }
+    Some(CompositeKind::OrderedListItem(depth, number))
+}
+
+/// Number the items of ordered lists: the first item of a list keeps
+/// its number, the next ones of the same depth follow it.
+///
+/// As the width of the number may change, the visible length of the
+/// renumbered items is recomputed
+pub(crate) fn number_ordered_lists(lines: &mut [FmtLine<'_>], skin: &MadSkin) {
+    let mut counters: Vec<Option<usize>> = Vec::new();
+    for line in lines {
+        let FmtLine::Normal(fc) = line else {
+            counters.clear();
+            continue;
+        };
+        match &mut fc.kind {
+            CompositeKind::OrderedListItem(depth, number) => {
+                let depth = *depth as usize;
+                counters.resize(depth + 1, None);
+                let renumbered = counters[depth].map_or(*number, |n| n + 1);
+                counters[depth] = Some(renumbered);
+                if renumbered != *number {
+                    *number = renumbered;
+                    fc.recompute_width(skin);
+                }
+            }
+            CompositeKind::ListItem(depth) | CompositeKind::TaskListItem(depth, _) => {
+                let depth = *depth as usize;
+                counters.resize(depth + 1, None);
+                counters[depth] = None;
+            }
+            _ => {
+                counters.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod list_numbering_tests {
+    use {
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_formats() {
+        let formats: Vec<String> = [1, 4, 9, 27, 1994]
+            .iter()
+            .map(|&n| {
+                format!(
+                    "{} {} {}",
+                    ListNumbering::Decimal.format(n),
+                    ListNumbering::LowerAlpha.format(n),
+                    ListNumbering::UpperRoman.format(n),
+                )
+            })
+            .collect();
+        assert_eq!(
+            formats,
+            vec!["1 a I", "4 d IV", "9 i IX", "27 aa XXVII", "1994 bxr MCMXCIV"],
+        );
+    }
+
+    #[test]
+    fn test_ordered_lists() {
+        let mut skin = MadSkin::no_style();
+        skin.list_numbering = vec![ListNumbering::Decimal, ListNumbering::LowerAlpha];
+        let md = "1. first item\n1. second item, long enough to be wrapped\n * sub\n 1) sub one\n 1) sub two\n3. third\n\n8. eight";
+        let text = FmtText::from(&skin, md, Some(24)).to_string();
+        assert_eq!(
+            text,
+            "1. first item\n2. second item, long \n   enough to be wrapped\n ◦ sub\n a. sub one\n b. sub two\n3. third\n\n8. eight\n",
+        );
+    }
+
+    #[test]
+    fn test_renumbered_item_width() {
+        let skin = MadSkin::no_style();
+        // "10. abcdefghi" exactly fits in 13 columns but not in 12
+        let md = "9. abcdefghi\n1. abcdefghi";
+        let text = FmtText::from(&skin, md, Some(13)).to_string();
+        assert_eq!(text, "9. abcdefghi\n10. abcdefghi\n");
+        let text = FmtText::from(&skin, md, Some(12));
+        assert!(text.lines.iter().all(|line| line.visible_length() <= 12));
+        assert_eq!(text.to_string(), "9. abcdefghi\n10. abcdefgh\n    i\n");
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    compound_range::SourcedCompound,
+    minimad::Compound,
+    CompoundRange,
+    FmtComposite,
+    FmtLine,
+};
+
+/// Why a part of a composite is displayed with a specific style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkKind {
+    SearchMatch,
+    CurrentSearchMatch,
+    Selection,
+}
+
+/// A marked part of a formatted composite, for example a search match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextMark {
+    pub range: CompoundRange,
+    pub kind: MarkKind,
+}
+
+impl TextMark {
+    /// Tell whether the compound of given index is part of the mark
+    pub fn contains(&self, idx: usize, compound: &Compound<'_>) -> bool {
+        self.range.contains(idx, compound)
+    }
+}
+
+/// return the composites of a line, in which the content
+/// can be searched or selected
+pub(crate) fn line_composites<'l, 's>(line: &'l FmtLine<'s>) -> Vec<&'l FmtComposite<'s>> {
+    match line {
+        FmtLine::Normal(fc) => vec![fc],
+        FmtLine::TableRow(row) => row.cells.iter().collect(),
+        FmtLine::FramedCode(line) => vec![&line.composite],
+        _ => Vec::new(),
+    }
+}
+
+/// return the composite of given index in a line, as
+/// given by [line_composites]
+pub(crate) fn line_composite_mut<'l, 's>(
+    line: &'l mut FmtLine<'s>,
+    idx: usize,
+) -> Option<&'l mut FmtComposite<'s>> {
+    match line {
+        FmtLine::Normal(fc) if idx == 0 => Some(fc),
+        FmtLine::TableRow(row) => row.cells.get_mut(idx),
+        FmtLine::FramedCode(line) if idx == 0 => Some(&mut line.composite),
+        _ => None,
+    }
+}
+
+/// Split the compounds of the composite at the bounds of the range,
+/// given in bytes in its concatenated compounds, and mark the range
+pub(crate) fn mark_range(fc: &mut FmtComposite<'_>, start: usize, end: usize, kind: MarkKind) {
+    let mut compounds = Vec::with_capacity(fc.compounds.len() + 2);
+    let mut offset = 0;
+    for (idx, compound) in fc.compounds.iter().enumerate() {
+        let len = compound.src.len();
+        let compound = SourcedCompound::new(idx, compound.clone());
+        let mut cuts = vec![0];
+        for bound in [start, end] {
+            if bound > offset && bound < offset + len {
+                cuts.push(bound - offset);
+            }
+        }
+        cuts.push(len);
+        for part in cuts.windows(2) {
+            if part[0] < part[1] || len == 0 {
+                compounds.push(compound.sub(part[0], part[1]));
+            }
+        }
+        offset += len;
+    }
+    fc.set_sourced_compounds(compounds);
+    // the range is now made of whole compounds
+    let mut range: Option<CompoundRange> = None;
+    let mut offset = 0;
+    for (idx, compound) in fc.compounds.iter().enumerate() {
+        let c_end = offset + compound.src.len();
+        if offset >= start && c_end <= end && c_end > offset {
+            let c_range = CompoundRange::of_compound(idx, compound);
+            range = Some(match range {
+                Some(range) => CompoundRange::new(range.start, c_range.end),
+                None => c_range,
+            });
+        }
+        offset = c_end;
+    }
+    if let Some(range) = range {
+        fc.marks.push(TextMark { range, kind });
+    }
+}
@@ -48,6 +48,8 @@ pub enum StyleToken {
     Attribute(Attribute),
     Align(Alignment),
     Dimension(u16),
+    /// Asks for the wrapped lines to be justified
+    Justify,
     /// A specified absence, meaning for example "no foreground"
     None,
 }
@@ -60,6 +62,7 @@ impl fmt::Display for StyleToken {
             Self::Attribute(a) => write_attribute(f, *a),
             Self::Align(a) => write_align(f, *a),
             Self::Dimension(number) => write!(f, "{}", number),
+            Self::Justify => write!(f, "justify"),
             Self::None => write!(f, "none"),
         }
     }
@@ -129,6 +132,9 @@ pub fn parse_style_token(s: &str) -> Result<StyleToken, ParseStyleTokenError> {
     if let Ok(attribute) = parse_attribute(s) {
         return Ok(StyleToken::Attribute(attribute));
     }
+    if regex_is_match!("^justif(y|ied)$"i, s) {
+        return Ok(StyleToken::Justify);
+    }
     if let Ok(align) = parse_align(s) {
         return Ok(StyleToken::Align(align));
     }
@@ -55,6 +55,9 @@ impl From<&[StyleToken]> for CompoundStyle {
                 StyleToken::Dimension(_) => {
                     // not of use for compound styles
                 }
+                StyleToken::Justify => {
+                    // not of use for compound styles
+                }
             }
         }
         style
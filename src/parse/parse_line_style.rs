@@ -15,11 +15,15 @@ impl From<&[StyleToken]> for LineStyle {
         let mut left_margin = None;
         let mut right_margin = None;
         let mut align = Default::default();
+        let mut justified = false;
         for token in tokens {
             match token {
                 StyleToken::Align(a) => {
                     align = *a;
                 }
+                StyleToken::Justify => {
+                    justified = true;
+                }
                 StyleToken::Dimension(number) => {
                     if left_margin.is_some() {
                         right_margin = Some(*number);
@@ -37,6 +41,7 @@ impl From<&[StyleToken]> for LineStyle {
             align,
             left_margin,
             right_margin,
+            justified,
         }
     }
 }
@@ -45,6 +50,9 @@ impl PushStyleTokens for LineStyle {
     fn push_style_tokens(&self, tokens: &mut Vec<StyleToken>) {
         self.compound_style.push_style_tokens(tokens);
         tokens.push(StyleToken::Align(self.align));
+        if self.justified {
+            tokens.push(StyleToken::Justify);
+        }
         if self.left_margin > 0 || self.right_margin > 0 {
             tokens.push(StyleToken::Dimension(self.left_margin.min(65536) as u16));
             tokens.push(StyleToken::Dimension(self.right_margin.min(65536) as u16));
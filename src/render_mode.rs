@@ -0,0 +1,149 @@
+use {
+    crate::{
+        crossterm::tty::IsTty,
+        grid::ansi::{
+            self,
+            Action,
+        },
+    },
+    std::{
+        ffi::OsStr,
+        sync::atomic::{
+            AtomicU8,
+            Ordering,
+        },
+    },
+};
+
+/// Whether the functions printing on stdout (like
+/// [MadSkin::print_text](crate::MadSkin::print_text)) write styles
+/// or plain text.
+///
+/// Plain text keeps the structure of the rendering (bullets, table
+/// borders, wrapping, alignment), only the styles (colors, bold, etc.)
+/// and the escape sequences are removed, and links are written with
+/// their URL in parentheses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Plain text when stdout isn't a terminal or when the `NO_COLOR`
+    /// environment variable is set (and not empty), styled otherwise
+    #[default]
+    Auto,
+    /// Always write styles
+    Styled,
+    /// Always write plain text
+    Plain,
+}
+
+static RENDER_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// Set the rendering mode of the functions printing on stdout,
+/// overriding the automatic detection unless `RenderMode::Auto`
+/// is passed
+pub fn set_render_mode(mode: RenderMode) {
+    RENDER_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Return the rendering mode set with [set_render_mode]
+pub fn render_mode() -> RenderMode {
+    match RENDER_MODE.load(Ordering::Relaxed) {
+        1 => RenderMode::Styled,
+        2 => RenderMode::Plain,
+        _ => RenderMode::Auto,
+    }
+}
+
+/// Tell whether the functions printing on stdout currently write
+/// plain text
+pub fn is_plain_output() -> bool {
+    let no_color = std::env::var_os("NO_COLOR");
+    render_mode().is_plain(no_color.as_deref(), std::io::stdout().is_tty())
+}
+
+impl RenderMode {
+    /// Tell whether this mode means plain text, given the value of
+    /// the `NO_COLOR` variable and whether stdout is a terminal
+    pub fn is_plain(self, no_color: Option<&OsStr>, stdout_is_tty: bool) -> bool {
+        match self {
+            Self::Auto => !stdout_is_tty || no_color.map_or(false, |v| !v.is_empty()),
+            Self::Styled => false,
+            Self::Plain => true,
+        }
+    }
+}
+
+/// Remove the escape sequences (styles, hyperlinks, cursor moves, etc.)
+/// of a string rendered for the terminal.
+///
+/// The ESC char starting an incomplete sequence is removed, what
+/// follows it is kept.
+pub fn strip_ansi(mut s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    while !s.is_empty() {
+        match ansi::parse_action(s) {
+            Some((action, len)) => {
+                if let Action::Print(c) = action {
+                    stripped.push(c);
+                }
+                s = &s[len..];
+            }
+            None => {
+                // an incomplete sequence, s starts with ESC
+                s = &s[1..];
+            }
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod render_mode_tests {
+    use {
+        crate::{
+            crossterm::style::Color,
+            *,
+        },
+        pretty_assertions::assert_eq,
+        std::ffi::OsStr,
+    };
+
+    #[test]
+    fn test_mode_resolution() {
+        assert!(!RenderMode::Auto.is_plain(None, true));
+        assert!(!RenderMode::Auto.is_plain(Some(OsStr::new("")), true));
+        assert!(RenderMode::Auto.is_plain(Some(OsStr::new("1")), true));
+        assert!(RenderMode::Auto.is_plain(None, false));
+        assert!(!RenderMode::Styled.is_plain(Some(OsStr::new("1")), false));
+        assert!(RenderMode::Plain.is_plain(None, true));
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("a \u{1b}[1mbold\u{1b}[0m word"), "a bold word");
+        assert_eq!(
+            strip_ansi("before \u{1b}]unterminated after"),
+            "before ]unterminated after",
+        );
+        assert_eq!(strip_ansi("end \u{1b}["), "end [");
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let mut skin = MadSkin::default();
+        skin.bullet.set_fg(Color::Red);
+        skin.link_rendering = LinkRendering::Hyperlink;
+        let md = "* **an item** with [a link](https://dystroy.org)\n|a|b|\n|-|-|\n|c|d|";
+        let skin = skin.plain_version();
+        let text = FmtText::from(&skin, md, Some(30));
+        assert_eq!(
+            strip_ansi(&text.to_string()),
+            concat!(
+                "• an item with a link \n",
+                "  (https://dystroy.org)\n",
+                "│ a │ b │\n",
+                "├───┼───┤\n",
+                "│c  │d  │\n",
+            ),
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     color::*,
+    color_depth::ColorDepth,
     crossterm::style::Color,
     styled_char::StyledChar,
 };
@@ -27,6 +28,10 @@ impl ScrollBarStyle {
         self.track.set_bg(bg);
         self.thumb.set_bg(bg);
     }
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        self.track.limit_to_color_depth(depth);
+        self.thumb.limit_to_color_depth(depth);
+    }
 }
 
 impl Default for ScrollBarStyle {
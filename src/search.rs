@@ -0,0 +1,401 @@
+use {
+    crate::{
+        mark::{
+            self,
+            MarkKind,
+        },
+        FmtComposite,
+        FmtLine,
+        FmtText,
+    },
+    lazy_regex::regex::{
+        self,
+        Regex,
+    },
+};
+
+/// What's searched in a text
+#[derive(Debug, Clone)]
+pub enum SearchPattern {
+    /// an exact string
+    Plain(String),
+    Regex(Regex),
+}
+
+impl SearchPattern {
+    /// Build a pattern searching for the exact given string
+    pub fn plain<S: Into<String>>(s: S) -> Self {
+        Self::Plain(s.into())
+    }
+    /// Build a pattern from a regular expression, for
+    /// example `"(?i)error"` for a case insensitive search
+    pub fn regex(s: &str) -> Result<Self, regex::Error> {
+        Ok(Self::Regex(Regex::new(s)?))
+    }
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Plain(s) => s.is_empty(),
+            Self::Regex(regex) => regex.as_str().is_empty(),
+        }
+    }
+    /// return the non empty byte ranges of the matches in `s`
+    fn find_all(&self, s: &str) -> Vec<(usize, usize)> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        match self {
+            Self::Plain(pattern) => s
+                .match_indices(pattern.as_str())
+                .map(|(start, m)| (start, start + m.len()))
+                .collect(),
+            Self::Regex(regex) => regex
+                .find_iter(s)
+                .filter(|m| !m.as_str().is_empty())
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+        }
+    }
+}
+
+/// A match of a search, in the visible text of a composite
+/// of a formatted text.
+///
+/// A match may span several lines when the composite was wrapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// index of the line of the start of the match in the formatted text
+    pub line: usize,
+    /// index of the cell, for a table row (0 otherwise)
+    pub cell: usize,
+    /// byte index of the start in the concatenated compounds of the
+    /// composite of the first line
+    pub start: usize,
+    /// index of the line of the end of the match
+    pub end_line: usize,
+    /// byte index of the end in the concatenated compounds of the
+    /// composite of the end line
+    pub end: usize,
+}
+
+/// A composite, in a line of the text, and the place of its compounds
+/// in a string joining wrapped lines
+struct JoinedPart {
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Return the composite at the given cell index of the line
+fn cell_composite<'l, 's>(line: &'l FmtLine<'s>, cell: usize) -> Option<&'l FmtComposite<'s>> {
+    mark::line_composites(line).get(cell).copied()
+}
+
+/// Return the position in a line of a byte index in the joined string
+fn joined_pos(parts: &[JoinedPart], idx: usize, is_end: bool) -> (usize, usize) {
+    let part = parts
+        .iter()
+        .find(|p| if is_end { idx <= p.end } else { idx < p.end })
+        .or_else(|| parts.last())
+        .expect("parts shouldn't be empty");
+    (part.line, idx.clamp(part.start, part.end) - part.start)
+}
+
+/// A search in a formatted text, with its matches and the
+/// current one, to be displayed in a [TextView](crate::TextView)
+/// or a [MadView](crate::MadView).
+///
+/// The matches must be updated with [TextSearch::update] when
+/// the text changes, for example on resize:
+///
+/// ```
+/// use termimad::*;
+///
+/// let skin = MadSkin::default();
+/// let area = Area::new(0, 0, 30, 5);
+/// let text = skin.area_text("a *needle*\nin a haystack of needles", &area);
+/// let mut search = TextSearch::new(SearchPattern::plain("needle"));
+/// search.update(&text);
+/// let mut view = TextView::from(&area, &text);
+/// view.search = Some(&search);
+/// assert_eq!(search.status(), "1/2");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextSearch {
+    pattern: SearchPattern,
+    matches: Vec<SearchMatch>,
+    current: usize,
+    /// the greatest number of lines a match spans, after the first one
+    max_span: usize,
+}
+
+impl TextSearch {
+    pub fn new(pattern: SearchPattern) -> Self {
+        Self {
+            pattern,
+            matches: Vec::new(),
+            current: 0,
+            max_span: 0,
+        }
+    }
+    pub fn pattern(&self) -> &SearchPattern {
+        &self.pattern
+    }
+    /// Change the pattern. The matches are cleared until the next update.
+    pub fn set_pattern(&mut self, pattern: SearchPattern) {
+        self.pattern = pattern;
+        self.matches.clear();
+        self.current = 0;
+        self.max_span = 0;
+    }
+    /// Compute the matches in the text, keeping the current
+    /// match index if possible.
+    ///
+    /// The lines resulting from wrapping are joined, so that
+    /// matches may span them.
+    pub fn update(&mut self, text: &FmtText<'_, '_>) {
+        self.matches.clear();
+        for (line_idx, line) in text.lines.iter().enumerate() {
+            for (cell, fc) in mark::line_composites(line).into_iter().enumerate() {
+                if fc.wrap_joint.is_some() {
+                    continue; // searched with the line it continues
+                }
+                let mut s: String = fc.compounds.iter().map(|c| c.src).collect();
+                let mut parts = vec![JoinedPart {
+                    line: line_idx,
+                    start: 0,
+                    end: s.len(),
+                }];
+                for (next_idx, next) in text.lines.iter().enumerate().skip(line_idx + 1) {
+                    let Some(next_fc) = cell_composite(next, cell) else {
+                        break;
+                    };
+                    let Some(joint) = next_fc.wrap_joint else {
+                        break;
+                    };
+                    s.push_str(joint.as_str());
+                    let start = s.len();
+                    for c in &next_fc.compounds {
+                        s.push_str(c.src);
+                    }
+                    parts.push(JoinedPart {
+                        line: next_idx,
+                        start,
+                        end: s.len(),
+                    });
+                }
+                for (start, end) in self.pattern.find_all(&s) {
+                    let (line, start) = joined_pos(&parts, start, false);
+                    let (end_line, end) = joined_pos(&parts, end, true);
+                    self.matches.push(SearchMatch {
+                        line,
+                        cell,
+                        start,
+                        end_line,
+                        end,
+                    });
+                }
+            }
+        }
+        self.matches.sort_by_key(|m| (m.line, m.cell, m.start));
+        self.max_span = self
+            .matches
+            .iter()
+            .map(|m| m.end_line - m.line)
+            .max()
+            .unwrap_or(0);
+        if self.current >= self.matches.len() {
+            self.current = 0;
+        }
+    }
+    pub fn matches(&self) -> &[SearchMatch] {
+        &self.matches
+    }
+    /// Return the number of matches
+    pub fn count(&self) -> usize {
+        self.matches.len()
+    }
+    /// Return the index of the current match, if there's a match
+    pub fn current_index(&self) -> Option<usize> {
+        if self.matches.is_empty() {
+            None
+        } else {
+            Some(self.current)
+        }
+    }
+    pub fn current(&self) -> Option<SearchMatch> {
+        self.matches.get(self.current).copied()
+    }
+    /// Make the next match current (going back to the first one
+    /// after the last one) and return it
+    pub fn select_next(&mut self) -> Option<SearchMatch> {
+        if !self.matches.is_empty() {
+            self.current = (self.current + 1) % self.matches.len();
+        }
+        self.current()
+    }
+    /// Make the previous match current (going to the last one
+    /// before the first one) and return it
+    pub fn select_previous(&mut self) -> Option<SearchMatch> {
+        if !self.matches.is_empty() {
+            self.current = (self.current + self.matches.len() - 1) % self.matches.len();
+        }
+        self.current()
+    }
+    /// Make current the first match at or after the given line
+    /// (or the first match if there's none)
+    pub fn select_from_line(&mut self, line: usize) -> Option<SearchMatch> {
+        self.current = self
+            .matches
+            .iter()
+            .position(|m| m.line >= line)
+            .unwrap_or(0);
+        self.current()
+    }
+    /// Return a short description of the search state, for a status
+    /// line, like `"3/12"`, or `"0/0"` when there's no match
+    pub fn status(&self) -> String {
+        match self.current_index() {
+            Some(idx) => format!("{}/{}", idx + 1, self.count()),
+            None => "0/0".to_string(),
+        }
+    }
+    /// Return a copy of the line with the compounds split at the
+    /// bounds of the matches and the matches marked, or None if
+    /// there's no match in this line
+    pub fn highlighted_line<'s>(&self, line_idx: usize, line: &FmtLine<'s>) -> Option<FmtLine<'s>> {
+        let start = self
+            .matches
+            .partition_point(|m| m.line + self.max_span < line_idx);
+        let end = self.matches.partition_point(|m| m.line <= line_idx);
+        let mut highlighted: Option<FmtLine<'s>> = None;
+        for (idx, m) in self.matches.iter().enumerate().take(end).skip(start) {
+            if m.end_line < line_idx {
+                continue;
+            }
+            let kind = if idx == self.current {
+                MarkKind::CurrentSearchMatch
+            } else {
+                MarkKind::SearchMatch
+            };
+            let m_start = if m.line == line_idx { m.start } else { 0 };
+            let m_end = if m.end_line == line_idx { m.end } else { usize::MAX };
+            let line = highlighted.get_or_insert_with(|| line.clone());
+            if let Some(fc) = mark::line_composite_mut(line, m.cell) {
+                mark::mark_range(fc, m_start, m_end, kind);
+            }
+        }
+        highlighted
+    }
+}
+
+#[cfg(test)]
+mod search_tests {
+    use {
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_search() {
+        let skin = MadSkin::no_style();
+        let md = "a *red* apple\n\n|fruit|color|\n|-|-|\n|apple|red|\n\nno match";
+        let text = FmtText::from(&skin, md, Some(30));
+        let mut search = TextSearch::new(SearchPattern::regex("re?d").unwrap());
+        search.update(&text);
+        assert_eq!(search.count(), 2);
+        assert_eq!(search.status(), "1/2");
+        let m = search.select_next().unwrap();
+        assert_eq!((m.cell, m.start, m.end), (1, 0, 3));
+        assert_eq!(search.status(), "2/2");
+        search.select_next();
+        assert_eq!(search.status(), "1/2");
+        search.select_previous();
+        assert_eq!(search.current(), Some(m));
+
+        // the first match is split between compounds
+        let mut search = TextSearch::new(SearchPattern::plain("d app"));
+        search.update(&text);
+        assert_eq!(search.count(), 1);
+        let line = search.highlighted_line(0, &text.lines[0]).unwrap();
+        let FmtLine::Normal(fc) = line else {
+            panic!("unexpected line");
+        };
+        let srcs: Vec<&str> = fc.compounds.iter().map(|c| c.src).collect();
+        assert_eq!(srcs, vec!["a ", "re", "d", " app", "le"]);
+        let highlighted: Vec<&str> = fc
+            .compounds
+            .iter()
+            .enumerate()
+            .filter(|(idx, c)| fc.marks.iter().any(|m| m.contains(*idx, c)))
+            .map(|(_, c)| c.src)
+            .collect();
+        assert_eq!(highlighted, vec!["d", " app"]);
+        assert!(search.highlighted_line(1, &text.lines[1]).is_none());
+    }
+
+    #[test]
+    fn test_text_view_scrolls_to_match() {
+        let mut skin = MadSkin::no_style();
+        skin.current_search_match = CompoundStyle::with_attr(crossterm::style::Attribute::Bold);
+        skin.scrollbar = ScrollBarStyle::new();
+        let md = "line 1\nline 2\nline 3\nline 4\nline 5\nline 6";
+        let area = Area::new(0, 0, 10, 2);
+        let text = skin.area_text(md, &area);
+        let mut search = TextSearch::new(SearchPattern::plain("5"));
+        search.update(&text);
+        let mut view = TextView::from(&area, &text);
+        view.show_search_match(&search);
+        assert_eq!(view.scroll, 3);
+        view.search = Some(&search);
+        let mut buffer = Buffer::new(10, 2);
+        view.write_on(&mut buffer).unwrap();
+        assert_eq!(buffer.to_plain_text(), "line 4   ▐\nline 5   ▐");
+        let bold = crossterm::style::Attribute::Bold;
+        assert!(buffer.cell(5, 1).unwrap().style.has_attr(bold));
+        assert!(!buffer.cell(4, 1).unwrap().style.has_attr(bold));
+    }
+
+    /// return the sources of the marked compounds of a highlighted line
+    fn highlighted(search: &TextSearch, text: &FmtText<'_, '_>, line_idx: usize) -> Vec<String> {
+        let Some(line) = search.highlighted_line(line_idx, &text.lines[line_idx]) else {
+            return Vec::new();
+        };
+        let FmtLine::Normal(fc) = line else {
+            panic!("unexpected line");
+        };
+        fc.compounds
+            .iter()
+            .enumerate()
+            .filter(|(idx, c)| fc.marks.iter().any(|m| m.contains(*idx, c)))
+            .map(|(_, c)| c.src.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_across_wraps() {
+        let skin = MadSkin::no_style();
+        let md = "find the *needle* in the haystack, a needle in a haystack";
+        let text = FmtText::from(&skin, md, Some(17));
+        assert_eq!(
+            text.to_string(),
+            "find the needle \nin the haystack, \na needle in a \nhaystack\n",
+        );
+        // matches spanning a wrap, at spaces or inside a word
+        let mut search = TextSearch::new(SearchPattern::plain("needle in"));
+        search.update(&text);
+        assert_eq!(search.count(), 2);
+        let m = search.matches()[0];
+        assert_eq!((m.line, m.start, m.end_line, m.end), (0, 9, 1, 2));
+        assert_eq!(highlighted(&search, &text, 0), vec!["needle", " "]);
+        assert_eq!(highlighted(&search, &text, 1), vec!["in"]);
+        let text = FmtText::from(&skin, "a needle in hay\u{AD}stack", Some(16));
+        assert_eq!(text.to_string(), "a needle in hay-\nstack\n");
+        let mut search = TextSearch::new(SearchPattern::plain("haystack"));
+        search.update(&text);
+        assert_eq!(search.count(), 1);
+        let m = search.matches()[0];
+        assert_eq!((m.line, m.start, m.end_line, m.end), (0, 12, 1, 5));
+        assert_eq!(highlighted(&search, &text, 0), vec!["hay"]);
+        assert_eq!(highlighted(&search, &text, 1), vec!["stack"]);
+    }
+}
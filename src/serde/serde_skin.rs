@@ -7,6 +7,7 @@ use {
         parse_styled_char,
         LineStyle,
         MadSkin,
+        StyledChar,
         TableBorderChars,
         ATTRIBUTES,
     },
@@ -20,6 +21,14 @@ use {
     std::fmt,
 };
 
+/// Parse a list of styled chars, for example the bullets of the list items
+fn parse_styled_chars<E: de::Error>(values: Vec<String>) -> Result<Vec<StyledChar>, E> {
+    values
+        .iter()
+        .map(|value| parse_styled_char(value, '*').map_err(de::Error::custom))
+        .collect()
+}
+
 impl<'de> de::Deserialize<'de> for MadSkin {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -67,6 +76,29 @@ impl<'de> de::Deserialize<'de> for MadSkin {
                             let cs = parse_compound_style(&value).map_err(de::Error::custom)?;
                             skin.ellipsis = cs;
                         }
+                        "link" => {
+                            let value = map.next_value::<String>()?;
+                            let cs = parse_compound_style(&value).map_err(de::Error::custom)?;
+                            skin.link = cs;
+                        }
+                        "link_rendering" | "link-rendering" => {
+                            skin.link_rendering = map.next_value()?;
+                        }
+                        "search_match" | "search-match" => {
+                            let value = map.next_value::<String>()?;
+                            let cs = parse_compound_style(&value).map_err(de::Error::custom)?;
+                            skin.search_match = cs;
+                        }
+                        "current_search_match" | "current-search-match" => {
+                            let value = map.next_value::<String>()?;
+                            let cs = parse_compound_style(&value).map_err(de::Error::custom)?;
+                            skin.current_search_match = cs;
+                        }
+                        "selection" => {
+                            let value = map.next_value::<String>()?;
+                            let cs = parse_compound_style(&value).map_err(de::Error::custom)?;
+                            skin.selection = cs;
+                        }
 
                         // marker chars
                         "bullet" => {
@@ -74,11 +106,35 @@ impl<'de> de::Deserialize<'de> for MadSkin {
                             let sc = parse_styled_char(&value, '*').map_err(de::Error::custom)?;
                             skin.bullet = sc;
                         }
+                        "nested_bullets" | "nested-bullets" => {
+                            skin.nested_bullets = parse_styled_chars(map.next_value()?)?;
+                        }
+                        "bullets" => {
+                            // all bullets, the first one being for the top level
+                            let mut bullets = parse_styled_chars(map.next_value()?)?;
+                            if !bullets.is_empty() {
+                                skin.bullet = bullets.remove(0);
+                                skin.nested_bullets = bullets;
+                            }
+                        }
+                        "checked" => {
+                            let value = map.next_value::<String>()?;
+                            let sc = parse_styled_char(&value, 'x').map_err(de::Error::custom)?;
+                            skin.checked = sc;
+                        }
+                        "unchecked" => {
+                            let value = map.next_value::<String>()?;
+                            let sc = parse_styled_char(&value, 'o').map_err(de::Error::custom)?;
+                            skin.unchecked = sc;
+                        }
                         "quote_mark" | "quote" | "quote-mark" => {
                             let value = map.next_value::<String>()?;
                             let sc = parse_styled_char(&value, '*').map_err(de::Error::custom)?;
                             skin.quote_mark = sc;
                         }
+                        "nested_quote_marks" | "nested-quote-marks" => {
+                            skin.nested_quote_marks = parse_styled_chars(map.next_value()?)?;
+                        }
                         "horizontal_rule" | "horizontal-rule" | "rule" => {
                             let value = map.next_value::<String>()?;
                             let sc = parse_styled_char(&value, '*').map_err(de::Error::custom)?;
@@ -108,6 +164,14 @@ impl<'de> de::Deserialize<'de> for MadSkin {
                             skin.table = ls;
                         }
 
+                        // highlighting of code blocks
+                        "code_highlights" | "code-highlights" => {
+                            skin.code_highlights = map.next_value()?;
+                        }
+                        "admonitions" => {
+                            skin.admonitions = map.next_value()?;
+                        }
+
                         // headers
                         "headers" => match map.next_value::<HeadersStyleInfo>()? {
                             HeadersStyleInfo::Add(ls) => {
@@ -148,6 +212,22 @@ impl<'de> de::Deserialize<'de> for MadSkin {
                                 skin.table_border_chars = chars;
                             }
                         }
+                        "code_block_border_chars" | "code-block-border-chars" => {
+                            let key = map.next_value::<String>()?;
+                            skin.code_block_border_chars = TableBorderChars::by_key(&key);
+                        }
+                        "code_block_line_numbers" | "code-block-line-numbers" => {
+                            skin.code_block_line_numbers = map.next_value()?;
+                        }
+                        "code_continuation_mark" | "code-continuation-mark" => {
+                            skin.code_continuation_mark = map.next_value()?;
+                        }
+                        "table_layout" | "table-layout" => {
+                            skin.table_layout = map.next_value()?;
+                        }
+                        "list_numbering" | "list-numbering" => {
+                            skin.list_numbering = map.next_value()?;
+                        }
 
                         _ => {
                             let _ = map.next_value::<String>()?;
@@ -176,10 +256,19 @@ impl Serialize for MadSkin {
         skin.serialize_entry("strikeout", &self.strikeout)?;
         skin.serialize_entry("inline_code", &self.inline_code)?;
         skin.serialize_entry("ellipsis", &self.ellipsis)?;
+        skin.serialize_entry("link", &self.link)?;
+        skin.serialize_entry("link_rendering", &self.link_rendering)?;
+        skin.serialize_entry("search_match", &self.search_match)?;
+        skin.serialize_entry("current_search_match", &self.current_search_match)?;
+        skin.serialize_entry("selection", &self.selection)?;
 
         // marker chars
         skin.serialize_entry("bullet", &self.bullet)?;
+        skin.serialize_entry("nested_bullets", &self.nested_bullets)?;
+        skin.serialize_entry("checked", &self.checked)?;
+        skin.serialize_entry("unchecked", &self.unchecked)?;
         skin.serialize_entry("quote", &self.quote_mark)?;
+        skin.serialize_entry("nested_quote_marks", &self.nested_quote_marks)?;
         skin.serialize_entry("horizontal_rule", &self.horizontal_rule)?;
 
         // scrollbar
@@ -191,6 +280,10 @@ impl Serialize for MadSkin {
         skin.serialize_entry("code_block", &self.code_block)?;
         skin.serialize_entry("table", &self.table)?;
 
+        // highlighting of code blocks
+        skin.serialize_entry("code_highlights", &self.code_highlights)?;
+        skin.serialize_entry("admonitions", &self.admonitions)?;
+
         // headers
         skin.serialize_entry("headers", &self.headers)?;
 
@@ -199,6 +292,13 @@ impl Serialize for MadSkin {
         if let Some(key) = self.table_border_chars.key() {
             skin.serialize_entry("table_border_chars", key)?;
         }
+        if let Some(key) = self.code_block_border_chars.and_then(|chars| chars.key()) {
+            skin.serialize_entry("code_block_border_chars", key)?;
+        }
+        skin.serialize_entry("code_block_line_numbers", &self.code_block_line_numbers)?;
+        skin.serialize_entry("code_continuation_mark", &self.code_continuation_mark)?;
+        skin.serialize_entry("table_layout", &self.table_layout)?;
+        skin.serialize_entry("list_numbering", &self.list_numbering)?;
 
         skin.end()
     }
@@ -223,7 +323,10 @@ fn skin_json_roundtrip() {
             },
             gray,
             rgb,
+            LinkRendering,
+            ListNumbering,
             StyledChar,
+            TableLayout,
             ROUNDED_TABLE_BORDER_CHARS,
         },
         pretty_assertions::assert_eq,
@@ -256,17 +359,46 @@ fn skin_json_roundtrip() {
     skin.bold.set_fg(Yellow);
     skin.italic.set_fgbg(Magenta, rgb(30, 30, 40));
     skin.bullet = StyledChar::from_fg_char(Yellow, '⟡');
+    skin.nested_bullets = vec![StyledChar::from_fg_char(Red, '◦')];
     skin.quote_mark.set_fg(Yellow);
+    skin.nested_quote_marks = vec![StyledChar::from_fg_char(Blue, '┃')];
+    skin.checked = StyledChar::from_fg_char(Green, '✔');
+    skin.unchecked.set_char('✗');
     skin.italic.set_fg(Magenta);
     skin.scrollbar.thumb.set_fg(AnsiValue(178));
     skin.table_border_chars = ROUNDED_TABLE_BORDER_CHARS;
+    skin.table_layout = TableLayout::Stacked;
+    skin.code_block_border_chars = Some(ROUNDED_TABLE_BORDER_CHARS);
+    skin.code_block_line_numbers = true;
+    skin.code_continuation_mark = StyledChar::from_fg_char(Red, '…');
+    skin.list_numbering = vec![ListNumbering::UpperRoman, ListNumbering::UpperAlpha];
     skin.paragraph.align = Alignment::Center;
+    skin.paragraph.justified = true;
     skin.table.align = Alignment::Center;
     skin.inline_code.add_attr(Attribute::Reverse);
     skin.paragraph.set_fgbg(Magenta, rgb(30, 30, 40));
     skin.italic.add_attr(Attribute::Underlined);
     skin.italic.add_attr(Attribute::OverLined);
+    skin.code_highlights.keyword.set_fg(Red);
+    skin.code_highlights.comment.add_attr(Attribute::Italic);
+    skin.admonitions.tip.mark.set_bg(Green);
+    skin.admonitions.caution.text.set_fg(Red);
+    skin.link.set_fg(Cyan);
+    skin.link_rendering = LinkRendering::Footnotes;
+    skin.search_match.set_bg(Yellow);
+    skin.current_search_match.add_attr(Attribute::Reverse);
+    skin.selection.set_bg(Blue);
     let serialized = serde_json::to_string_pretty(&skin).unwrap();
     let deserialized = serde_json::from_str(&serialized).unwrap();
     assert_eq!(skin, deserialized);
 }
+
+/// Check the bullets of a skin file are used according to the
+/// depth of the list items
+#[test]
+fn skin_file_bullets() {
+    let hjson = r#"bullets: ["- red", "+ blue"]"#;
+    let skin: MadSkin = deser_hjson::from_str(hjson).unwrap();
+    let bullets: String = (0..4).map(|depth| skin.bullet_for_depth(depth).nude_char()).collect();
+    assert_eq!(bullets, "-+-+");
+}
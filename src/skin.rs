@@ -9,6 +9,7 @@ use {
             },
         },
         errors::Result,
+        link,
         table_border_chars::*,
         tbl::*,
         *,
@@ -24,10 +25,14 @@ use {
         MAX_HEADER_DEPTH,
     },
     std::{
+        borrow::Cow,
         fmt,
         io::Write,
     },
-    unicode_width::UnicodeWidthStr,
+    unicode_width::{
+        UnicodeWidthChar,
+        UnicodeWidthStr,
+    },
 };
 
 /// A skin defining how a parsed markdown appears on the terminal
@@ -44,11 +49,52 @@ pub struct MadSkin {
     pub scrollbar: ScrollBarStyle,
     pub table: LineStyle, // the compound style is for border chars
     pub bullet: StyledChar,
+    /// bullets of the list items of depth 1 and more, cycling
+    /// back to `bullet` after the last one
+    pub nested_bullets: Vec<StyledChar>,
+    /// replaces the bullet of checked task list items
+    pub checked: StyledChar,
+    /// replaces the bullet of unchecked task list items
+    pub unchecked: StyledChar,
     pub quote_mark: StyledChar,
+    /// marks of the nested levels of quotes, cycling back to
+    /// `quote_mark` after the last one
+    pub nested_quote_marks: Vec<StyledChar>,
+    /// styles of the GitHub style admonitions (`> [!NOTE]`, etc.)
+    pub admonitions: AdmonitionStyles,
     pub horizontal_rule: StyledChar,
     pub ellipsis: CompoundStyle,
+    /// style of the text of markdown links
+    pub link: CompoundStyle,
+    pub link_rendering: LinkRendering,
+    /// style of the matches of a search in a view
+    pub search_match: CompoundStyle,
+    /// style of the current match of a search in a view
+    pub current_search_match: CompoundStyle,
+    /// style of the text selected in a view
+    pub selection: CompoundStyle,
     pub table_border_chars: &'static TableBorderChars,
+    pub table_layout: TableLayout,
+    /// when set, code blocks are framed with these chars, with the
+    /// language of the fence, if any, in the top border
+    pub code_block_border_chars: Option<&'static TableBorderChars>,
+    /// whether to display line numbers in framed code blocks
+    pub code_block_line_numbers: bool,
+    /// starts the continuation lines of wrapped code lines
+    pub code_continuation_mark: StyledChar,
     pub list_items_indentation_mode: ListItemsIndentationMode,
+    /// numbering styles of ordered lists, per depth (cycling
+    /// when the list is deeper)
+    pub list_numbering: Vec<ListNumbering>,
+    /// when set, tells where long words may be hyphenated when
+    /// wrapping lines
+    pub hyphenator: Option<&'static dyn Hyphenator>,
+
+    /// the highlighter of fenced code blocks with a language,
+    /// None to disable highlighting
+    pub code_highlighter: Option<&'static dyn CodeHighlighter>,
+    /// styles of the tokens of highlighted code blocks
+    pub code_highlights: CodeHighlightStyles,
 
     /// compounds which should be replaced with special
     /// renders.
@@ -59,6 +105,33 @@ pub struct MadSkin {
     pub special_chars: std::collections::HashMap<Compound<'static>, StyledChar>,
 }
 
+/// A part written by [MadSkin::write_fmt_composite] before the compounds
+pub(crate) enum LeadingPart<'k> {
+    /// spaces, with a style
+    Spaces(&'k CompoundStyle, usize),
+    /// a styled char, like a bullet or a quote mark
+    Char(&'k StyledChar),
+    /// a styled text, like the mark of an item of an ordered list
+    Text(CompoundStyle, String),
+}
+
+impl LeadingPart<'_> {
+    fn width(&self) -> usize {
+        match self {
+            Self::Spaces(_, count) => *count,
+            Self::Char(sc) => UnicodeWidthChar::width(sc.nude_char()).unwrap_or(0),
+            Self::Text(_, text) => text.width(),
+        }
+    }
+    fn write(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spaces(style, count) => style.repeat_space(f, *count),
+            Self::Char(sc) => write!(f, "{}", sc),
+            Self::Text(style, text) => write!(f, "{}", style.apply_to(text)),
+        }
+    }
+}
+
 impl Default for MadSkin {
     /// Build a customizable skin.
     ///
@@ -80,14 +153,46 @@ impl Default for MadSkin {
             scrollbar: ScrollBarStyle::new(),
             table: CompoundStyle::with_fg(gray(7)).into(),
             bullet: StyledChar::from_fg_char(gray(8), '•'),
+            nested_bullets: vec![
+                StyledChar::from_fg_char(gray(8), '◦'),
+                StyledChar::from_fg_char(gray(8), '▪'),
+            ],
+            checked: StyledChar::from_fg_char(gray(8), '☑'),
+            unchecked: StyledChar::from_fg_char(gray(8), '☐'),
             quote_mark: StyledChar::new(
                 CompoundStyle::new(Some(gray(12)), None, Attribute::Bold.into()),
                 '▐',
             ),
+            nested_quote_marks: vec![StyledChar::new(
+                CompoundStyle::new(Some(gray(9)), None, Attribute::Bold.into()),
+                '▐',
+            )],
+            admonitions: AdmonitionStyles::default(),
             horizontal_rule: StyledChar::from_fg_char(gray(6), '―'),
             ellipsis: CompoundStyle::default(),
+            link: CompoundStyle::new(Some(ansi(75)), None, Attribute::Underlined.into()),
+            link_rendering: LinkRendering::Markdown,
+            search_match: CompoundStyle::with_fgbg(gray(2), ansi(136)),
+            current_search_match: CompoundStyle::new(
+                Some(gray(2)),
+                Some(ansi(214)),
+                Attribute::Bold.into(),
+            ),
+            selection: CompoundStyle::with_fgbg(gray(23), ansi(24)),
             table_border_chars: STANDARD_TABLE_BORDER_CHARS,
+            table_layout: TableLayout::Auto,
+            code_block_border_chars: None,
+            code_block_line_numbers: false,
+            code_continuation_mark: StyledChar::from_fg_char(gray(8), '↪'),
             list_items_indentation_mode: Default::default(),
+            list_numbering: vec![
+                ListNumbering::Decimal,
+                ListNumbering::LowerAlpha,
+                ListNumbering::LowerRoman,
+            ],
+            hyphenator: None,
+            code_highlighter: Some(&BUILTIN_HIGHLIGHTER),
+            code_highlights: CodeHighlightStyles::default(),
 
             #[cfg(feature = "special-renders")]
             special_chars: std::collections::HashMap::new(),
@@ -121,13 +226,35 @@ impl MadSkin {
             scrollbar: ScrollBarStyle::new(),
             table: LineStyle::default(),
             bullet: StyledChar::nude('•'),
+            nested_bullets: vec![StyledChar::nude('◦'), StyledChar::nude('▪')],
+            checked: StyledChar::nude('☑'),
+            unchecked: StyledChar::nude('☐'),
             quote_mark: StyledChar::nude('▐'),
+            nested_quote_marks: Vec::new(),
+            admonitions: AdmonitionStyles::no_style(),
             horizontal_rule: StyledChar::nude('―'),
             ellipsis: CompoundStyle::default(),
+            link: CompoundStyle::default(),
+            link_rendering: LinkRendering::Markdown,
+            search_match: CompoundStyle::default(),
+            current_search_match: CompoundStyle::default(),
+            selection: CompoundStyle::default(),
             list_items_indentation_mode: Default::default(),
+            list_numbering: vec![
+                ListNumbering::Decimal,
+                ListNumbering::LowerAlpha,
+                ListNumbering::LowerRoman,
+            ],
+            hyphenator: None,
+            code_highlighter: Some(&BUILTIN_HIGHLIGHTER),
+            code_highlights: CodeHighlightStyles::no_style(),
             #[cfg(feature = "special-renders")]
             special_chars: std::collections::HashMap::new(),
             table_border_chars: STANDARD_TABLE_BORDER_CHARS,
+            table_layout: TableLayout::Auto,
+            code_block_border_chars: None,
+            code_block_line_numbers: false,
+            code_continuation_mark: StyledChar::nude('↪'),
         }
     }
 
@@ -165,9 +292,75 @@ impl MadSkin {
     /// to be in the non extended ASCII range
     pub fn limit_to_ascii(&mut self) {
         self.table_border_chars = ASCII_TABLE_BORDER_CHARS;
+        if self.code_block_border_chars.is_some() {
+            self.code_block_border_chars = Some(ASCII_TABLE_BORDER_CHARS);
+        }
         self.bullet.set_char('*');
+        for (i, bullet) in self.nested_bullets.iter_mut().enumerate() {
+            bullet.set_char(if i % 2 == 0 { '-' } else { '+' });
+        }
+        self.checked.set_char('x');
+        self.unchecked.set_char('o');
         self.quote_mark.set_char('>');
+        for mark in &mut self.nested_quote_marks {
+            mark.set_char('>');
+        }
+        self.admonitions.set_mark_char('>');
         self.horizontal_rule.set_char('-');
+        self.code_continuation_mark.set_char('~');
+    }
+
+    /// Replace all the colors of the skin with the nearest ones which
+    /// can be displayed with the given color depth.
+    ///
+    /// This is useful when the skin uses RGB colors but the terminal
+    /// doesn't support them.
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        if depth == ColorDepth::TrueColor {
+            return;
+        }
+        self.paragraph.limit_to_color_depth(depth);
+        self.bold.limit_to_color_depth(depth);
+        self.italic.limit_to_color_depth(depth);
+        self.strikeout.limit_to_color_depth(depth);
+        self.inline_code.limit_to_color_depth(depth);
+        self.code_block.limit_to_color_depth(depth);
+        for h in &mut self.headers {
+            h.limit_to_color_depth(depth);
+        }
+        self.scrollbar.limit_to_color_depth(depth);
+        self.table.limit_to_color_depth(depth);
+        self.bullet.limit_to_color_depth(depth);
+        for bullet in &mut self.nested_bullets {
+            bullet.limit_to_color_depth(depth);
+        }
+        self.checked.limit_to_color_depth(depth);
+        self.unchecked.limit_to_color_depth(depth);
+        self.quote_mark.limit_to_color_depth(depth);
+        for mark in &mut self.nested_quote_marks {
+            mark.limit_to_color_depth(depth);
+        }
+        self.admonitions.limit_to_color_depth(depth);
+        self.horizontal_rule.limit_to_color_depth(depth);
+        self.ellipsis.limit_to_color_depth(depth);
+        self.link.limit_to_color_depth(depth);
+        self.search_match.limit_to_color_depth(depth);
+        self.current_search_match.limit_to_color_depth(depth);
+        self.selection.limit_to_color_depth(depth);
+        self.code_continuation_mark.limit_to_color_depth(depth);
+        self.code_highlights.limit_to_color_depth(depth);
+        #[cfg(feature = "special-renders")]
+        {
+            for (_, sc) in self.special_chars.iter_mut() {
+                sc.limit_to_color_depth(depth);
+            }
+        }
+    }
+
+    /// Replace the colors of the skin with the nearest ones the terminal
+    /// can display, according to [color_depth]
+    pub fn limit_to_terminal_colors(&mut self) {
+        self.limit_to_color_depth(color_depth());
     }
 
     /// Blend the foreground and background colors (if any) into the given dest color,
@@ -181,15 +374,30 @@ impl MadSkin {
         self.italic.blend_with(color, weight);
         self.inline_code.blend_with(color, weight);
         self.code_block.blend_with(color, weight);
+        self.code_highlights.blend_with(color, weight);
+        self.admonitions.blend_with(color, weight);
         self.table.compound_style.blend_with(color, weight);
         self.strikeout.blend_with(color, weight);
         for h in &mut self.headers {
             h.blend_with(color, weight);
         }
         self.bullet.blend_with(color, weight);
+        for bullet in &mut self.nested_bullets {
+            bullet.blend_with(color, weight);
+        }
+        self.checked.blend_with(color, weight);
+        self.unchecked.blend_with(color, weight);
         self.quote_mark.blend_with(color, weight);
+        for mark in &mut self.nested_quote_marks {
+            mark.blend_with(color, weight);
+        }
         self.horizontal_rule.blend_with(color, weight);
+        self.code_continuation_mark.blend_with(color, weight);
         self.ellipsis.blend_with(color, weight);
+        self.link.blend_with(color, weight);
+        self.search_match.blend_with(color, weight);
+        self.current_search_match.blend_with(color, weight);
+        self.selection.blend_with(color, weight);
     }
 
     /// Change the foreground of most styles (the ones which commonly
@@ -205,7 +413,15 @@ impl MadSkin {
         self.strikeout.set_fg(fg);
         self.set_headers_fg(fg);
         self.bullet.set_fg(fg);
+        for bullet in &mut self.nested_bullets {
+            bullet.set_fg(fg);
+        }
+        self.checked.set_fg(fg);
+        self.unchecked.set_fg(fg);
         self.quote_mark.set_fg(fg);
+        for mark in &mut self.nested_quote_marks {
+            mark.set_fg(fg);
+        }
         self.horizontal_rule.set_fg(fg);
         self.ellipsis.set_fg(fg);
         #[cfg(feature = "special-renders")]
@@ -230,7 +446,15 @@ impl MadSkin {
         self.set_headers_bg(bg);
         self.table.compound_style.set_bg(bg);
         self.bullet.set_bg(bg);
+        for bullet in &mut self.nested_bullets {
+            bullet.set_bg(bg);
+        }
+        self.checked.set_bg(bg);
+        self.unchecked.set_bg(bg);
         self.quote_mark.set_bg(bg);
+        for mark in &mut self.nested_quote_marks {
+            mark.set_bg(bg);
+        }
         self.horizontal_rule.set_bg(bg);
         self.ellipsis.set_bg(bg);
         self.scrollbar.set_bg(bg);
@@ -279,15 +503,73 @@ impl MadSkin {
         let compounds_width: usize = compounds.iter().map(|c| c.src.width()).sum();
         (match kind {
             CompositeKind::ListItem(depth) => 2 + depth as usize, // space and bullet
+            CompositeKind::TaskListItem(depth, _) => 2 + depth as usize, // space and checkbox
             CompositeKind::ListItemFollowUp(depth) => match self.list_items_indentation_mode {
                 ListItemsIndentationMode::FirstLineOnly => 0,
                 ListItemsIndentationMode::Block => 2 + depth as usize, // spaces
             },
-            CompositeKind::Quote => 2, // space of the quoting char
+            CompositeKind::OrderedListItem(depth, number) => {
+                self.ordered_list_item_indent(depth, number)
+            }
+            CompositeKind::OrderedListItemFollowUp(depth, number) => {
+                match self.list_items_indentation_mode {
+                    ListItemsIndentationMode::FirstLineOnly => 0,
+                    ListItemsIndentationMode::Block => self.ordered_list_item_indent(depth, number),
+                }
+            }
+            CompositeKind::Quote(depth) => 2 * depth as usize, // quoting chars and spaces
+            CompositeKind::Admonition(_) | CompositeKind::AdmonitionTitle(_) => 2,
+            CompositeKind::CodeFollowUp(indent) => indent + self.code_continuation_width(),
+            CompositeKind::Footnote(number) => link::footnote_mark_width(number) + 1,
+            CompositeKind::StackedCell(column) => tbl::column_label(column).width() + 2,
             _ => 0,
         }) + compounds_width
     }
 
+    /// Return the width written between the indentation and the code of
+    /// the continuation of a wrapped code line: continuation mark and space
+    pub fn code_continuation_width(&self) -> usize {
+        UnicodeWidthChar::width(self.code_continuation_mark.nude_char()).unwrap_or(0) + 1
+    }
+
+    /// Return the bullet of the list items of the given depth
+    pub fn bullet_for_depth(&self, depth: u8) -> &StyledChar {
+        let idx = depth as usize % (self.nested_bullets.len() + 1);
+        if idx == 0 {
+            &self.bullet
+        } else {
+            &self.nested_bullets[idx - 1]
+        }
+    }
+
+    /// Return the quote mark of the given level of a quote (0 being
+    /// the outer level)
+    pub fn quote_mark_for_depth(&self, level: u8) -> &StyledChar {
+        let idx = level as usize % (self.nested_quote_marks.len() + 1);
+        if idx == 0 {
+            &self.quote_mark
+        } else {
+            &self.nested_quote_marks[idx - 1]
+        }
+    }
+
+    /// Return the mark of an item of an ordered list, for example `"3."`
+    /// or `"c."`, according to the numbering style of the depth
+    pub fn ordered_list_item_mark(&self, depth: u8, number: usize) -> String {
+        let numbering = if self.list_numbering.is_empty() {
+            ListNumbering::Decimal
+        } else {
+            self.list_numbering[depth as usize % self.list_numbering.len()]
+        };
+        format!("{}.", numbering.format(number))
+    }
+
+    /// Return the width taken before the content of an item of an
+    /// ordered list: indentation, mark and space
+    pub fn ordered_list_item_indent(&self, depth: u8, number: usize) -> usize {
+        depth as usize + self.ordered_list_item_mark(depth, number).width() + 1
+    }
+
     // FIXME deprecate ?
     pub fn visible_line_length(&self, line: &Line<'_>) -> usize {
         match line {
@@ -301,7 +583,7 @@ impl MadSkin {
     /// return the style to apply to a given line
     pub const fn line_style(&self, kind: CompositeKind) -> &LineStyle {
         match kind {
-            CompositeKind::Code => &self.code_block,
+            CompositeKind::Code | CompositeKind::CodeFollowUp(_) => &self.code_block,
             CompositeKind::Header(level) if level <= MAX_HEADER_DEPTH as u8 => {
                 &self.headers[level as usize - 1]
             }
@@ -331,11 +613,154 @@ impl MadSkin {
         os
     }
 
+    /// return the style of a text written in a formatted composite
+    /// without being one of its compounds (like a footnote mark)
+    pub(crate) fn fmt_composite_style(
+        &self,
+        line_style: &LineStyle,
+        fc: &FmtComposite<'_>,
+    ) -> CompoundStyle {
+        let mut os = line_style.compound_style.clone();
+        self.overwrite_with_kind_style(&mut os, fc.kind);
+        os
+    }
+
+    /// apply the styles specific to the kind of a composite
+    fn overwrite_with_kind_style(&self, os: &mut CompoundStyle, kind: CompositeKind) {
+        match kind {
+            CompositeKind::AdmonitionTitle(kind) => {
+                os.overwrite_with(&self.admonitions.style(kind).title);
+            }
+            CompositeKind::Admonition(kind) => {
+                os.overwrite_with(&self.admonitions.style(kind).text);
+            }
+            _ => {}
+        }
+    }
+
+    /// return the style of the compound of given index of a formatted
+    /// composite, taking into account the highlighting of code, links and marks
+    pub(crate) fn fmt_compound_style(
+        &self,
+        line_style: &LineStyle,
+        fc: &FmtComposite<'_>,
+        idx: usize,
+    ) -> CompoundStyle {
+        let compound = &fc.compounds[idx];
+        let mut os = self.compound_style(line_style, compound);
+        self.overwrite_with_kind_style(&mut os, fc.kind);
+        if let Some(token) = fc.code_tokens.iter().find(|t| t.contains(idx, compound)) {
+            os.overwrite_with(self.code_highlights.style(token.kind));
+        }
+        if fc.links.iter().any(|l| l.contains(idx, compound)) {
+            os.overwrite_with(&self.link);
+        }
+        for mark in fc.marks.iter().filter(|m| m.contains(idx, compound)) {
+            os.overwrite_with(self.mark_style(mark.kind));
+        }
+        os
+    }
+
+    /// return the style of the compounds marked with the given kind
+    pub const fn mark_style(&self, kind: MarkKind) -> &CompoundStyle {
+        match kind {
+            MarkKind::SearchMatch => &self.search_match,
+            MarkKind::CurrentSearchMatch => &self.current_search_match,
+            MarkKind::Selection => &self.selection,
+        }
+    }
+
+    /// return the URL of the hyperlink the compound of given index is
+    /// part of, if the skin renders links as hyperlinks
+    pub(crate) fn fmt_compound_hyperlink<'s>(
+        &self,
+        fc: &FmtComposite<'s>,
+        idx: usize,
+    ) -> Option<&'s str> {
+        match self.link_rendering {
+            LinkRendering::Hyperlink => fc
+                .links
+                .iter()
+                .find(|l| l.contains(idx, &fc.compounds[idx]) && l.is_url_safe())
+                .map(|l| l.url),
+            _ => None,
+        }
+    }
+
+    /// write the compound of given index of a formatted composite,
+    /// followed by the spaces added by justification
+    fn write_fmt_compound(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        line_style: &LineStyle,
+        fc: &FmtComposite<'_>,
+        idx: usize,
+    ) -> fmt::Result {
+        let compound = &fc.compounds[idx];
+        let os = self.fmt_compound_style(line_style, fc, idx);
+        match fc.padding_after(idx) {
+            0 => write!(f, "{}", os.apply_to(compound.as_str())),
+            padding => {
+                let content = format!("{}{}", compound.as_str(), " ".repeat(padding));
+                write!(f, "{}", os.apply_to(content))
+            }
+        }
+    }
+
+    /// write the compounds of a formatted composite, the consecutive
+    /// compounds of a link being written in one hyperlink when the
+    /// skin says so
+    fn write_fmt_compounds(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        line_style: &LineStyle,
+        fc: &FmtComposite<'_>,
+    ) -> fmt::Result {
+        let mut open_hyperlink = None;
+        for (idx, c) in fc.compounds.iter().enumerate() {
+            let hyperlink = self.fmt_compound_hyperlink(fc, idx);
+            if hyperlink != open_hyperlink {
+                if open_hyperlink.is_some() {
+                    link::write_hyperlink_end(f)?;
+                }
+                if let Some(url) = hyperlink {
+                    link::write_hyperlink_start(f, url)?;
+                }
+                open_hyperlink = hyperlink;
+            }
+            #[cfg(feature = "special-renders")]
+            if let Some(replacement) = self.special_chars.get(c) {
+                write!(f, "{}", replacement)?;
+                continue;
+            }
+            self.write_fmt_compound(f, line_style, fc, idx)?;
+            if let Some(number) = fc.footnote_after(idx) {
+                if open_hyperlink.take().is_some() {
+                    link::write_hyperlink_end(f)?;
+                }
+                let os = self.fmt_composite_style(line_style, fc);
+                write!(f, "{}", os.apply_to(link::footnote_mark(number)))?;
+            }
+        }
+        if fc.hyphenated {
+            let style = match fc.compounds.len() {
+                0 => line_style.compound_style.clone(),
+                len => self.fmt_compound_style(line_style, fc, len - 1),
+            };
+            write!(f, "{}", style.apply_to('-'))?;
+        }
+        if open_hyperlink.is_some() {
+            link::write_hyperlink_end(f)?;
+        }
+        Ok(())
+    }
+
     /// return a formatted line or part of line.
     ///
     /// Don't use this function if `src` is expected to be several lines.
     pub fn inline<'k, 's>(&'k self, src: &'s str) -> FmtInline<'k, 's> {
-        let composite = FmtComposite::from(Composite::from_inline(src), self);
+        let mut composite = FmtComposite::from(Composite::from_inline(src), self);
+        line_break::remove_soft_hyphens(&mut composite, self);
         FmtInline {
             skin: self,
             composite,
@@ -381,26 +806,64 @@ impl MadSkin {
         view.write_on(w)
     }
 
+    /// Return a copy of the skin fit for rendering plain text: links
+    /// rendered as hyperlinks get their URL in parentheses instead.
+    ///
+    /// The styles are kept, they're removed from the rendered string
+    /// with [strip_ansi].
+    pub fn plain_version(&self) -> Self {
+        let mut skin = self.clone();
+        if skin.link_rendering == LinkRendering::Hyperlink {
+            skin.link_rendering = LinkRendering::UrlInParentheses;
+        }
+        skin
+    }
+
+    /// Return the skin to use for printing on stdout, according
+    /// to the current [RenderMode]
+    fn stdout_skin(&self) -> Cow<'_, Self> {
+        if is_plain_output() {
+            Cow::Owned(self.plain_version())
+        } else {
+            Cow::Borrowed(self)
+        }
+    }
+
+    /// do a `print!` of the given rendering, without its styles
+    /// if the output is plain (see [RenderMode])
+    fn print_rendered<D: fmt::Display>(rendered: D) {
+        if is_plain_output() {
+            print!("{}", strip_ansi(&rendered.to_string()));
+        } else {
+            print!("{}", rendered);
+        }
+    }
+
     /// do a `print!` of the given src interpreted as a markdown span
     ///
     /// Don't use this function if the string is expected to be several
     /// lines or have typed lines (titles, bullets, code fences, etc.):
     /// use `print_text` instead.
+    ///
+    /// Styles are written or not according to the [RenderMode].
     pub fn print_inline(&self, src: &str) {
-        print!("{}", self.inline(src));
+        Self::print_rendered(self.stdout_skin().inline(src));
     }
 
     /// do a `print!` of the given src interpreted as a markdown text
+    ///
+    /// Styles are written or not according to the [RenderMode].
     pub fn print_text(&self, src: &str) {
-        print!("{}", self.term_text(src));
+        Self::print_rendered(self.stdout_skin().term_text(src));
     }
 
     /// do a `print!` of the given expander
     pub fn print_expander(&self, expander: TextTemplateExpander<'_, '_>) {
         let (width, _) = terminal_size();
         let text = expander.expand();
-        let fmt_text = FmtText::from_text(self, text, Some(width as usize));
-        print!("{}", fmt_text);
+        let skin = self.stdout_skin();
+        let fmt_text = FmtText::from_text(&skin, text, Some(width as usize));
+        Self::print_rendered(fmt_text);
     }
 
     /// do a `print!` of the given owning expander
@@ -411,8 +874,9 @@ impl MadSkin {
     ) {
         let (width, _) = terminal_size();
         let text = expander.expand(template);
-        let fmt_text = FmtText::from_text(self, text, Some(width as usize));
-        print!("{}", fmt_text);
+        let skin = self.stdout_skin();
+        let fmt_text = FmtText::from_text(&skin, text, Some(width as usize));
+        Self::print_rendered(fmt_text);
     }
 
     /// do a `print!` of the given owning expander
@@ -425,18 +889,17 @@ impl MadSkin {
         let template_md: String = template.into();
         let template = TextTemplate::from(&*template_md);
         let text = expander.expand(&template);
-        let fmt_text = FmtText::from_text(self, text, Some(width as usize));
-        print!("{}", fmt_text);
+        let skin = self.stdout_skin();
+        let fmt_text = FmtText::from_text(&skin, text, Some(width as usize));
+        Self::print_rendered(fmt_text);
     }
 
     pub fn print_composite(&self, composite: Composite<'_>) {
-        print!(
-            "{}",
-            FmtInline {
-                skin: self,
-                composite: FmtComposite::from(composite, self),
-            }
-        );
+        let skin = self.stdout_skin();
+        Self::print_rendered(FmtInline {
+            skin: &skin,
+            composite: FmtComposite::from(composite, &skin),
+        });
     }
 
     pub fn write_composite<W>(&self, w: &mut W, composite: Composite<'_>) -> Result<()>
@@ -490,18 +953,30 @@ impl MadSkin {
         Ok(queue!(w, Print(self.term_text(src)))?)
     }
 
-    /// parse the given src as a markdown snippet and write it on stdout
+    /// parse the given src as a markdown snippet and write it on stdout,
+    /// with styles or not according to the [RenderMode]
     pub fn write_inline(&self, src: &str) -> Result<()> {
         let mut w = std::io::stdout();
-        self.write_inline_on(&mut w, src)?;
+        let skin = self.stdout_skin();
+        if is_plain_output() {
+            write!(w, "{}", strip_ansi(&skin.inline(src).to_string()))?;
+        } else {
+            skin.write_inline_on(&mut w, src)?;
+        }
         w.flush()?;
         Ok(())
     }
 
-    /// parse the given src as a markdown text and write it on stdout
+    /// parse the given src as a markdown text and write it on stdout,
+    /// with styles or not according to the [RenderMode]
     pub fn write_text(&self, src: &str) -> Result<()> {
         let mut w = std::io::stdout();
-        self.write_text_on(&mut w, src)?;
+        let skin = self.stdout_skin();
+        if is_plain_output() {
+            write!(w, "{}", strip_ansi(&skin.term_text(src).to_string()))?;
+        } else {
+            skin.write_text_on(&mut w, src)?;
+        }
         w.flush()?;
         Ok(())
     }
@@ -518,6 +993,29 @@ impl MadSkin {
         with_right_completion: bool,
         with_margins: bool,
     ) -> fmt::Result {
+        let ls = self.line_style(fc.kind);
+        let (leading, rpi, rpo) = self.fmt_composite_leading(fc, outer_width, with_margins);
+        for part in &leading {
+            part.write(f)?;
+        }
+        self.write_fmt_compounds(f, ls, fc)?;
+        ls.compound_style.repeat_space(f, rpi)?;
+        if with_right_completion {
+            self.paragraph.repeat_space(f, rpo)?;
+        }
+        Ok(())
+    }
+
+    /// Return what [MadSkin::write_fmt_composite] writes before the
+    /// compounds (margin, completion, bullet, quote marks, etc.), and
+    /// the numbers of spaces it writes after them, inside and outside
+    /// the composite
+    pub(crate) fn fmt_composite_leading(
+        &self,
+        fc: &FmtComposite<'_>,
+        outer_width: Option<usize>,
+        with_margins: bool,
+    ) -> (Vec<LeadingPart<'_>>, usize, usize) {
         let ls = self.line_style(fc.kind);
         let (left_margin, right_margin) = if with_margins {
             ls.margins_in(outer_width)
@@ -531,44 +1029,211 @@ impl MadSkin {
             inner_width + left_margin + right_margin,
             outer_width,
         );
-        self.paragraph.repeat_space(f, lpo + left_margin)?;
-        ls.compound_style.repeat_space(f, lpi)?;
-        if let CompositeKind::ListItem(depth) = fc.kind {
-            for _ in 0..depth {
-                write!(f, "{}", self.paragraph.compound_style.apply_to(' '))?;
-            }
-            write!(f, "{}", self.bullet)?;
-            write!(f, "{}", self.paragraph.compound_style.apply_to(' '))?;
-        }
-        if self.list_items_indentation_mode == ListItemsIndentationMode::Block {
-            if let CompositeKind::ListItemFollowUp(depth) = fc.kind {
-                for _ in 0..depth + 1 {
-                    write!(f, "{}", self.paragraph.compound_style.apply_to(' '))?;
+        let space = &self.paragraph.compound_style;
+        let mut parts = vec![
+            LeadingPart::Spaces(space, lpo + left_margin),
+            LeadingPart::Spaces(&ls.compound_style, lpi),
+        ];
+        match fc.kind {
+            CompositeKind::ListItem(depth) => {
+                parts.push(LeadingPart::Spaces(space, depth as usize));
+                parts.push(LeadingPart::Char(self.bullet_for_depth(depth)));
+                parts.push(LeadingPart::Spaces(space, 1));
+            }
+            CompositeKind::TaskListItem(depth, checked) => {
+                parts.push(LeadingPart::Spaces(space, depth as usize));
+                parts.push(LeadingPart::Char(if checked {
+                    &self.checked
+                } else {
+                    &self.unchecked
+                }));
+                parts.push(LeadingPart::Spaces(space, 1));
+            }
+            CompositeKind::OrderedListItem(depth, number) => {
+                let style = self.bullet_for_depth(depth).compound_style().clone();
+                parts.push(LeadingPart::Spaces(space, depth as usize));
+                parts.push(LeadingPart::Text(style, self.ordered_list_item_mark(depth, number)));
+                parts.push(LeadingPart::Spaces(space, 1));
+            }
+            CompositeKind::ListItemFollowUp(depth)
+                if self.list_items_indentation_mode == ListItemsIndentationMode::Block =>
+            {
+                parts.push(LeadingPart::Spaces(space, depth as usize + 2));
+            }
+            CompositeKind::OrderedListItemFollowUp(depth, number)
+                if self.list_items_indentation_mode == ListItemsIndentationMode::Block =>
+            {
+                let indent = self.ordered_list_item_indent(depth, number);
+                parts.push(LeadingPart::Spaces(space, indent));
+            }
+            CompositeKind::Quote(depth) => {
+                for level in 0..depth {
+                    parts.push(LeadingPart::Char(self.quote_mark_for_depth(level)));
+                    parts.push(LeadingPart::Spaces(space, 1));
                 }
-                write!(f, "{}", self.paragraph.compound_style.apply_to(' '))?;
             }
+            CompositeKind::CodeFollowUp(indent) => {
+                parts.push(LeadingPart::Spaces(&ls.compound_style, indent));
+                parts.push(LeadingPart::Char(&self.code_continuation_mark));
+                parts.push(LeadingPart::Spaces(&ls.compound_style, 1));
+            }
+            CompositeKind::Admonition(kind) | CompositeKind::AdmonitionTitle(kind) => {
+                parts.push(LeadingPart::Char(&self.admonitions.style(kind).mark));
+                parts.push(LeadingPart::Spaces(space, 1));
+            }
+            CompositeKind::Footnote(number) => {
+                let style = ls.compound_style.clone();
+                parts.push(LeadingPart::Text(style, link::footnote_mark(number)));
+                parts.push(LeadingPart::Spaces(&ls.compound_style, 1));
+            }
+            CompositeKind::StackedCell(column) => {
+                let mut label_style = ls.compound_style.clone();
+                label_style.overwrite_with(&self.bold);
+                parts.push(LeadingPart::Text(label_style, tbl::column_label(column)));
+                parts.push(LeadingPart::Text(ls.compound_style.clone(), ": ".to_string()));
+            }
+            _ => {}
         }
-        if fc.kind == CompositeKind::Quote {
-            write!(f, "{}", self.quote_mark)?;
-            write!(f, "{}", self.paragraph.compound_style.apply_to(' '))?;
+        (parts, rpi, rpo + right_margin)
+    }
+
+    /// Return the number of cells written by [MadSkin::write_fmt_composite]
+    /// before the compounds (margins, bullet, etc.)
+    pub(crate) fn fmt_composite_content_start(
+        &self,
+        fc: &FmtComposite<'_>,
+        outer_width: Option<usize>,
+        with_margins: bool,
+    ) -> usize {
+        let (leading, _, _) = self.fmt_composite_leading(fc, outer_width, with_margins);
+        leading.iter().map(LeadingPart::width).sum()
+    }
+
+    /// Return the number of cells written by [MadSkin::write_fmt_line] before
+    /// the compounds of each composite of the line (one composite for a normal
+    /// line, one per cell for a table row, none for other lines)
+    pub(crate) fn fmt_line_content_starts(
+        &self,
+        line: &FmtLine<'_>,
+        width: Option<usize>,
+    ) -> Vec<usize> {
+        match line {
+            FmtLine::Normal(fc) => vec![self.fmt_composite_content_start(fc, width, true)],
+            FmtLine::TableRow(FmtTableRow { cells }) => {
+                let char_width = |c: char| UnicodeWidthChar::width(c).unwrap_or(0);
+                let cell_width = |cell: &FmtComposite<'_>| {
+                    cell.spacing.map_or(cell.visible_length, |sp| sp.width)
+                };
+                let tbl_width = 1 + cells.iter().map(|cell| cell_width(cell) + 1).sum::<usize>();
+                let (lpo, _) = Spacing::optional_completions(self.table.align, tbl_width, width);
+                let border_width = char_width(self.table_border_chars.vertical);
+                let mut x = lpo;
+                let mut starts = Vec::with_capacity(cells.len());
+                for cell in cells {
+                    x += border_width;
+                    starts.push(x + self.fmt_composite_content_start(cell, None, false));
+                    x += cell_width(cell);
+                }
+                starts
+            }
+            FmtLine::FramedCode(line) => {
+                let (lpo, _) = self.code_frame_completions(line.visible_length(), width);
+                let mut x = lpo;
+                if let Some(bc) = self.code_block_border_chars {
+                    x += UnicodeWidthChar::width(bc.vertical).unwrap_or(0) + 1;
+                    if line.gutter_width > 0 {
+                        x += line.gutter_width + 1;
+                        x += UnicodeWidthChar::width(bc.vertical).unwrap_or(0) + 1;
+                    }
+                }
+                vec![x + self.fmt_composite_content_start(&line.composite, None, false)]
+            }
+            _ => Vec::new(),
         }
-        #[cfg(feature = "special-renders")]
-        for c in &fc.compounds {
-            if let Some(replacement) = self.special_chars.get(c) {
-                write!(f, "{}", replacement)?;
-            } else {
-                let os = self.compound_style(ls, c);
-                write!(f, "{}", os.apply_to(c.as_str()))?;
+    }
+
+    /// Return the spaces to write left and right of a line of a framed
+    /// code block, including the margins of the code block style
+    pub(crate) fn code_frame_completions(&self, line_width: usize, width: Option<usize>) -> (usize, usize) {
+        let (left_margin, right_margin) = self.code_block.margins_in(width);
+        let (lpo, rpo) = Spacing::optional_completions(
+            self.code_block.align,
+            line_width + left_margin + right_margin,
+            width,
+        );
+        (lpo + left_margin, rpo + right_margin)
+    }
+
+    fn write_code_frame(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        frame: &FmtCodeFrame<'_>,
+        width: Option<usize>,
+        with_right_completion: bool,
+    ) -> fmt::Result {
+        let Some(bc) = self.code_block_border_chars else {
+            return Ok(());
+        };
+        let (lpo, rpo) = self.code_frame_completions(frame.visible_length(), width);
+        self.paragraph.repeat_space(f, lpo)?;
+        let (left, junction, right) = match frame.position {
+            RelativePosition::Bottom => (
+                bc.bottom_left_corner,
+                bc.bottom_junction,
+                bc.bottom_right_corner,
+            ),
+            _ => (bc.top_left_corner, bc.top_junction, bc.top_right_corner),
+        };
+        let border = &self.table.compound_style;
+        write!(f, "{}", border.apply_to(left))?;
+        if frame.gutter_width > 0 {
+            self.table.repeat_char(f, bc.horizontal, frame.gutter_width + 2)?;
+            write!(f, "{}", border.apply_to(junction))?;
+        }
+        let mut code_part = frame.width + 2;
+        if let RelativePosition::Top = frame.position {
+            if let Some(label) = code::top_border_label(frame.lang, frame.width) {
+                write!(f, "{}", border.apply_to(bc.horizontal))?;
+                write!(f, "{}", border.apply_to(&label))?;
+                code_part -= 1 + label.width();
             }
         }
-        #[cfg(not(feature = "special-renders"))]
-        for c in &fc.compounds {
-            let os = self.compound_style(ls, c);
-            write!(f, "{}", os.apply_to(c.as_str()))?;
+        self.table.repeat_char(f, bc.horizontal, code_part)?;
+        write!(f, "{}", border.apply_to(right))?;
+        if with_right_completion {
+            self.paragraph.repeat_space(f, rpo)?;
         }
-        ls.compound_style.repeat_space(f, rpi)?;
+        Ok(())
+    }
+
+    fn write_framed_code(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        line: &FmtFramedCode<'_>,
+        width: Option<usize>,
+        with_right_completion: bool,
+    ) -> fmt::Result {
+        let Some(bc) = self.code_block_border_chars else {
+            return self.write_fmt_composite(f, &line.composite, width, with_right_completion, true);
+        };
+        let (lpo, rpo) = self.code_frame_completions(line.visible_length(), width);
+        self.paragraph.repeat_space(f, lpo)?;
+        let border = &self.table.compound_style;
+        write!(f, "{}", border.apply_to(bc.vertical))?;
+        self.paragraph.repeat_space(f, 1)?;
+        if line.gutter_width > 0 {
+            let number = line.number.map_or_else(String::new, |n| n.to_string());
+            let number = format!("{:>w$}", number, w = line.gutter_width);
+            write!(f, "{}", border.apply_to(number))?;
+            self.paragraph.repeat_space(f, 1)?;
+            write!(f, "{}", border.apply_to(bc.vertical))?;
+            self.paragraph.repeat_space(f, 1)?;
+        }
+        self.write_fmt_composite(f, &line.composite, None, false, false)?;
+        self.paragraph.repeat_space(f, 1)?;
+        write!(f, "{}", border.apply_to(bc.vertical))?;
         if with_right_completion {
-            self.paragraph.repeat_space(f, rpo + right_margin)?;
+            self.paragraph.repeat_space(f, rpo)?;
         }
         Ok(())
     }
@@ -656,6 +1321,12 @@ impl MadSkin {
                     write!(f, "{}", self.horizontal_rule.repeated(w))?;
                 }
             }
+            FmtLine::CodeFrame(frame) => {
+                self.write_code_frame(f, frame, width, with_right_completion)?;
+            }
+            FmtLine::FramedCode(line) => {
+                self.write_framed_code(f, line, width, with_right_completion)?;
+            }
         }
         Ok(())
     }
@@ -0,0 +1,271 @@
+use {
+    crate::{
+        area::Area,
+        crossterm::{
+            cursor::MoveTo,
+            queue,
+            style::Print,
+            terminal::{
+                Clear,
+                ClearType,
+            },
+        },
+        errors::Result,
+        list_numbering,
+        skin::MadSkin,
+        task_list,
+        text::FmtText,
+    },
+    minimad::{
+        parser::LineParser,
+        CompositeStyle,
+        Line,
+    },
+    std::io::Write,
+};
+
+/// The kinds of blocks whose lines are formatted together, and
+/// thus can't be rendered before the block is complete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Table,
+    Code,
+    List,
+    Quote,
+}
+
+fn block_kind(line: &Line<'_>) -> Option<BlockKind> {
+    match line {
+        Line::TableRow(_) | Line::TableRule(_) => Some(BlockKind::Table),
+        Line::Normal(composite) => match composite.style {
+            CompositeStyle::Code => Some(BlockKind::Code),
+            CompositeStyle::ListItem(_) => Some(BlockKind::List),
+            // a quote may be an admonition, whose tag is on the first line
+            CompositeStyle::Quote => Some(BlockKind::Quote),
+            CompositeStyle::Paragraph
+                if list_numbering::is_ordered_item(&composite.compounds)
+                    || task_list::is_task_item(&composite.compounds) =>
+            {
+                Some(BlockKind::List)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Return the length of the start of the source made of complete
+/// lines and blocks, which won't change when more text is appended
+fn stable_len(src: &str) -> usize {
+    let mut stable = 0;
+    let mut start = 0;
+    let mut in_fence = false;
+    let mut open_block = None;
+    while let Some(len) = src[start..].find('\n') {
+        let end = start + len + 1;
+        let line = LineParser::from(&src[start..start + len]).line();
+        if let Line::CodeFence(..) = line {
+            if in_fence {
+                stable = end;
+            } else {
+                stable = start;
+                open_block = None;
+            }
+            in_fence = !in_fence;
+        } else if !in_fence {
+            let kind = block_kind(&line);
+            if kind.is_none() {
+                stable = end;
+            } else if kind != open_block {
+                stable = start;
+            }
+            open_block = kind;
+        }
+        start = end;
+    }
+    stable
+}
+
+/// A markdown text received in chunks, for example the output of
+/// a long running process, rendered progressively.
+///
+/// Complete lines are rendered as soon as they can't be changed by
+/// the following ones, which means that code fences, tables, indented
+/// code and lists are rendered when they're closed. The unfinished end
+/// of the text is re-parsed and re-wrapped on each new chunk, but
+/// not the stable lines.
+///
+/// ```
+/// use termimad::*;
+///
+/// let skin = MadSkin::no_style();
+/// let mut stream = StreamingText::new(&skin, Some(40));
+/// let mut w = Vec::new();
+/// for chunk in ["# Tit", "le\n|a|b|\n", "|-|-|\n|1|2|\n", "\nend\n"] {
+///     stream.push_str(chunk);
+///     stream.write_new_lines_on(&mut w).unwrap();
+/// }
+/// stream.finish();
+/// stream.write_new_lines_on(&mut w).unwrap();
+/// assert_eq!(
+///     String::from_utf8(w).unwrap(),
+///     FmtText::from(&skin, "# Title\n|a|b|\n|-|-|\n|1|2|\n\nend\n", Some(40)).to_string(),
+/// );
+/// ```
+///
+/// Note that when the skin renders links as footnotes, the URLs are listed
+/// after each rendered chunk.
+pub struct StreamingText<'k> {
+    skin: &'k MadSkin,
+    width: Option<usize>,
+    /// the source not yet rendered as stable lines
+    pending_src: String,
+    /// the rendered lines which won't change
+    stable_lines: Vec<String>,
+    /// number of stable lines already returned or written
+    taken: usize,
+    /// the current rendering of the pending source
+    pending_lines: Vec<String>,
+}
+
+impl<'k> StreamingText<'k> {
+    /// Create an empty text, to be rendered with the given skin and width
+    pub fn new(skin: &'k MadSkin, width: Option<usize>) -> Self {
+        Self {
+            skin,
+            width,
+            pending_src: String::new(),
+            stable_lines: Vec::new(),
+            taken: 0,
+            pending_lines: Vec::new(),
+        }
+    }
+    fn render(&self, src: &str) -> Vec<String> {
+        let text = FmtText::from(self.skin, src, self.width).to_string();
+        text.split_terminator('\n').map(String::from).collect()
+    }
+    /// Append a chunk of markdown, which doesn't have to end
+    /// with a complete line
+    pub fn push_str(&mut self, chunk: &str) {
+        self.pending_src.push_str(chunk);
+        let stable_len = stable_len(&self.pending_src);
+        if stable_len > 0 {
+            let lines = self.render(&self.pending_src[..stable_len]);
+            self.stable_lines.extend(lines);
+            self.pending_src.drain(..stable_len);
+        }
+        self.pending_lines = self.render(&self.pending_src);
+    }
+    /// Consider the text complete: the pending lines (for example
+    /// an unclosed code fence or a last line without newline) become stable
+    pub fn finish(&mut self) {
+        let lines = std::mem::take(&mut self.pending_lines);
+        self.stable_lines.extend(lines);
+        self.pending_src.clear();
+    }
+    /// Return the stable lines which weren't returned or written before
+    pub fn take_new_lines(&mut self) -> &[String] {
+        let start = self.taken;
+        self.taken = self.stable_lines.len();
+        &self.stable_lines[start..]
+    }
+    /// Write the stable lines which weren't returned or written before,
+    /// each one followed by a newline
+    pub fn write_new_lines_on<W: Write>(&mut self, w: &mut W) -> Result<()> {
+        for line in self.take_new_lines() {
+            writeln!(w, "{}", line)?;
+        }
+        Ok(())
+    }
+    /// Return the rendered lines which won't change anymore
+    pub fn stable_lines(&self) -> &[String] {
+        &self.stable_lines
+    }
+    /// Return the current rendering of the unfinished end of
+    /// the text, which may change with the next chunks
+    pub fn pending_lines(&self) -> &[String] {
+        &self.pending_lines
+    }
+    /// Return all the rendered lines, stable then pending
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.stable_lines
+            .iter()
+            .chain(self.pending_lines.iter())
+            .map(String::as_str)
+    }
+    pub fn line_count(&self) -> usize {
+        self.stable_lines.len() + self.pending_lines.len()
+    }
+    /// Write the end of the text in the area, like a `tail -f`.
+    ///
+    /// The text should have been created with the width of the area, and
+    /// the area should extend to the right of the screen, as the ends of
+    /// the lines are cleared.
+    pub fn write_tail_in_area_on<W: Write>(&self, w: &mut W, area: &Area) -> Result<()> {
+        let height = area.height as usize;
+        let skip = self.line_count().saturating_sub(height);
+        let mut lines = self.lines().skip(skip);
+        for y in area.top..area.top + area.height {
+            queue!(w, MoveTo(area.left, y))?;
+            if let Some(line) = lines.next() {
+                queue!(w, Print(line))?;
+            }
+            queue!(w, Clear(ClearType::UntilNewLine))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod streaming_text_tests {
+    use {
+        super::*,
+        pretty_assertions::assert_eq,
+    };
+
+    static MD: &str = r#"# Title
+Some *text* long enough to be wrapped
+* item 1
+* item 2
+```rust
+fn main() {}
+```
+|a|b|
+|-|-|
+|1|2|
+    indented code
+    with two lines
+The end"#;
+
+    #[test]
+    fn test_stable_len() {
+        assert_eq!(stable_len("a\nb"), 2);
+        assert_eq!(stable_len("a\n* b\n* c\n"), 2);
+        assert_eq!(stable_len("a\n* b\n* c\nd\n"), 12);
+        assert_eq!(stable_len("a\n```\nb\n"), 2);
+        assert_eq!(stable_len("a\n```\nb\n```\n"), 12);
+        assert_eq!(stable_len("|a|\n|-|\n* b\n"), 8);
+    }
+
+    #[test]
+    fn test_chunked_rendering() {
+        // whatever the chunks, the result is the one of the whole text
+        let skin = MadSkin::default();
+        let expected = FmtText::from(&skin, MD, Some(25)).to_string();
+        for chunk_len in [1, 3, 7, 1000] {
+            let mut stream = StreamingText::new(&skin, Some(25));
+            let mut w = Vec::new();
+            let chars: Vec<char> = MD.chars().collect();
+            for chunk in chars.chunks(chunk_len) {
+                let chunk: String = chunk.iter().collect();
+                stream.push_str(&chunk);
+                stream.write_new_lines_on(&mut w).unwrap();
+            }
+            // the indented code isn't closed before "The end" is complete
+            assert_eq!(stream.pending_lines().len(), 3);
+            stream.finish();
+            stream.write_new_lines_on(&mut w).unwrap();
+            assert_eq!(String::from_utf8(w).unwrap(), expected);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use {
+    crate::crossterm::{
+        style::{
+            Color,
+            Colored,
+            ContentStyle,
+            SetAttributes,
+            SetBackgroundColor,
+            SetForegroundColor,
+            SetUnderlineColor,
+            StyledContent,
+        },
+        Command,
+    },
+    std::fmt::{
+        self,
+        Display,
+    },
+};
+
+/// Some content with a style, as built by
+/// [CompoundStyle::apply_to](crate::CompoundStyle::apply_to).
+///
+/// It's displayed like crossterm's `StyledContent`, except that the 16
+/// named colors (`Color::Red`, `Color::DarkBlue`, etc.) are written with
+/// their standard codes (`91`, `44`, etc.) instead of 256 colors codes,
+/// so that they're understood by terminals limited to 16 colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Styled<D> {
+    style: ContentStyle,
+    content: D,
+}
+
+impl<D: Display> Styled<D> {
+    pub const fn new(style: ContentStyle, content: D) -> Self {
+        Self { style, content }
+    }
+    pub const fn style(&self) -> &ContentStyle {
+        &self.style
+    }
+    pub const fn content(&self) -> &D {
+        &self.content
+    }
+}
+
+/// Return the code of the color in the 16 colors palette, if
+/// it's one of its named colors
+fn palette_code(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Black => 0,
+        Color::DarkRed => 1,
+        Color::DarkGreen => 2,
+        Color::DarkYellow => 3,
+        Color::DarkBlue => 4,
+        Color::DarkMagenta => 5,
+        Color::DarkCyan => 6,
+        Color::Grey => 7,
+        Color::DarkGrey => 8,
+        Color::Red => 9,
+        Color::Green => 10,
+        Color::Yellow => 11,
+        Color::Blue => 12,
+        Color::Magenta => 13,
+        Color::Cyan => 14,
+        Color::White => 15,
+        _ => return None,
+    })
+}
+
+/// Write the SGR sequence setting the foreground (`base` being 30)
+/// or background (`base` being 40) color
+fn write_color<W: fmt::Write>(w: &mut W, color: Color, base: u8) -> fmt::Result {
+    match palette_code(color) {
+        Some(_) if Colored::ansi_color_disabled_memoized() => Ok(()),
+        Some(code) if code < 8 => write!(w, "\u{1b}[{}m", base + code),
+        Some(code) => write!(w, "\u{1b}[{}m", base + 60 + code - 8),
+        None if base == 30 => SetForegroundColor(color).write_ansi(w),
+        None => SetBackgroundColor(color).write_ansi(w),
+    }
+}
+
+/// Write the SGR sequence setting the foreground color
+pub(crate) fn write_fg<W: fmt::Write>(w: &mut W, color: Color) -> fmt::Result {
+    write_color(w, color, 30)
+}
+
+/// Write the SGR sequence setting the background color
+pub(crate) fn write_bg<W: fmt::Write>(w: &mut W, color: Color) -> fmt::Result {
+    write_color(w, color, 40)
+}
+
+impl<D: Display> Display for Styled<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let style = &self.style;
+        let named = |color: Option<Color>| color.and_then(palette_code).is_some();
+        if !named(style.foreground_color) && !named(style.background_color) {
+            return StyledContent::new(*style, &self.content).fmt(f);
+        }
+        // same sequences as crossterm's PrintStyledContent, but
+        // with the standard codes of the named colors
+        if let Some(bg) = style.background_color {
+            write_bg(f, bg)?;
+        }
+        if let Some(fg) = style.foreground_color {
+            write_fg(f, fg)?;
+        }
+        if let Some(ul) = style.underline_color {
+            SetUnderlineColor(ul).write_ansi(f)?;
+        }
+        if !style.attributes.is_empty() {
+            SetAttributes(style.attributes).write_ansi(f)?;
+            write!(f, "{}", self.content)?;
+            return f.write_str("\u{1b}[0m");
+        }
+        write!(f, "{}", self.content)?;
+        if style.background_color.is_some() {
+            write_bg(f, Color::Reset)?;
+        }
+        if style.foreground_color.is_some() || style.underline_color.is_some() {
+            write_fg(f, Color::Reset)?;
+        }
+        Ok(())
+    }
+}
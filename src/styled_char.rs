@@ -1,15 +1,16 @@
 use {
     crate::{
+        color_depth::ColorDepth,
         compound_style::CompoundStyle,
         crossterm::{
             style::{
                 Color,
-                PrintStyledContent,
-                StyledContent,
+                Print,
             },
             QueueableCommand,
         },
         errors::Result,
+        styled::Styled,
     },
     std::{
         fmt::{
@@ -26,7 +27,7 @@ use {
 pub struct StyledChar {
     compound_style: CompoundStyle,
     nude_char: char,
-    styled_char: StyledContent<char>, // redundant, kept for performance
+    styled_char: Styled<char>, // redundant, kept for performance
 }
 
 impl StyledChar {
@@ -80,7 +81,7 @@ impl StyledChar {
     }
     /// Return a struct implementing `Display`, made of a (optimized) repetition
     ///  of the character with its style.
-    pub fn repeated(&self, count: usize) -> StyledContent<String> {
+    pub fn repeated(&self, count: usize) -> Styled<String> {
         let mut s = String::new();
         for _ in 0..count {
             s.push(self.nude_char);
@@ -95,12 +96,19 @@ impl StyledChar {
         self.compound_style.queue(w, s)
     }
     pub fn queue<W: Write>(&self, w: &mut W) -> Result<()> {
-        w.queue(PrintStyledContent(self.styled_char))?;
+        w.queue(Print(self.styled_char))?;
         Ok(())
     }
     pub fn blend_with<C: Into<coolor::Color>>(&mut self, color: C, weight: f32) {
         self.compound_style.blend_with(color, weight);
     }
+    /// Replace the colors with the nearest ones which can be
+    /// displayed with the given color depth
+    pub fn limit_to_color_depth(&mut self, depth: ColorDepth) {
+        let mut compound_style = self.compound_style.clone();
+        compound_style.limit_to_color_depth(depth);
+        self.set_compound_style(compound_style);
+    }
 }
 
 impl Display for StyledChar {
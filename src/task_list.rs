@@ -0,0 +1,64 @@
+use {
+    crate::CompositeKind,
+    lazy_regex::*,
+    minimad::Compound,
+};
+
+/// If the compounds start with a checkbox (like `"[x] "`), or with a
+/// bullet and a checkbox for a paragraph (like `"- [ ] "`, as minimad
+/// only parses `*` bullets), return the depth, the checked state and
+/// the length of the mark
+fn task_item_mark(kind: CompositeKind, compounds: &[Compound<'_>]) -> Option<(u8, bool, usize)> {
+    let first = compounds.first().filter(|c| !c.code)?;
+    match kind {
+        CompositeKind::ListItem(depth) => {
+            let (mark, check) = regex_captures!(r"^\[([ xX])\] ", first.src)?;
+            Some((depth, check != " ", mark.len()))
+        }
+        CompositeKind::Paragraph => {
+            let (mark, indent, check) = regex_captures!(r"^( {0,3})[-*] \[([ xX])\] ", first.src)?;
+            Some((indent.len() as u8, check != " ", mark.len()))
+        }
+        _ => None,
+    }
+}
+
+/// Tell whether the compounds of a paragraph make an item of a task list
+pub(crate) fn is_task_item(compounds: &[Compound<'_>]) -> bool {
+    task_item_mark(CompositeKind::Paragraph, compounds).is_some()
+}
+
+/// If the compounds of a list item or paragraph start with a checkbox,
+/// remove it and return the kind of the task item
+pub(crate) fn extract_task_item(
+    kind: CompositeKind,
+    compounds: &mut Vec<Compound<'_>>,
+) -> Option<CompositeKind> {
+    let (depth, checked, len) = task_item_mark(kind, compounds)?;
+    compounds[0].src = &compounds[0].src[len..];
+    if compounds[0].src.is_empty() {
+        compounds.remove(0);
+    }
+    Some(CompositeKind::TaskListItem(depth, checked))
+}
+
+#[cfg(test)]
+mod task_list_tests {
+    use {
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    #[test]
+    fn test_task_list() {
+        let mut skin = MadSkin::no_style();
+        skin.checked = StyledChar::nude('x');
+        skin.unchecked = StyledChar::nude('o');
+        let md = "* [x] done\n* [ ] todo, long enough to be wrapped\n- [X] done too\n * [ ] sub task\n* [y] not a task";
+        let text = FmtText::from(&skin, md, Some(24)).to_string();
+        assert_eq!(
+            text,
+            "x done\no todo, long enough to \n  be wrapped\nx done too\n o sub task\n• [y] not a task\n",
+        );
+    }
+}
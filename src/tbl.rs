@@ -1,6 +1,7 @@
 use {
     crate::{
         composite::*,
+        composite_kind::CompositeKind,
         fit::{
             wrap,
             TblFit,
@@ -11,18 +12,39 @@ use {
     },
     minimad::{
         Alignment,
+        Compound,
         TableRow,
     },
+    serde::{
+        Deserialize,
+        Serialize,
+    },
 };
 
+/// How tables are laid out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TableLayout {
+    /// As a grid when all columns can be displayed, with at least
+    /// 3 cells each, and stacked otherwise
+    #[default]
+    Auto,
+    /// Always as a grid, dropping the last columns when
+    /// the width is too small
+    Grid,
+    /// Each row is displayed as a block of "header: value" lines,
+    /// the blocks being separated with rules
+    Stacked,
+}
+
 /// Wrap a standard table row
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FmtTableRow<'s> {
     pub cells: Vec<FmtComposite<'s>>,
 }
 
 /// Top, Bottom, or other
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum RelativePosition {
     Top,
     Other, // or unknown
@@ -33,7 +55,7 @@ pub enum RelativePosition {
 ///
 /// Represent this kind of lines in tables:
 ///  |----|:-:|--
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FmtTableRule {
     pub position: RelativePosition, // position relative to the table
     pub widths: Vec<usize>,
@@ -82,11 +104,67 @@ struct Table {
 
 #[allow(clippy::needless_range_loop)]
 impl Table {
+    /// Tell whether the table must be displayed as stacked records
+    fn is_stacked(&self, width: usize, skin: &MadSkin) -> bool {
+        match skin.table_layout {
+            TableLayout::Auto => width < self.nbcols * 4 + 1,
+            TableLayout::Grid => false,
+            TableLayout::Stacked => true,
+        }
+    }
+    /// Replace the lines of the table with blocks of "header: value"
+    /// lines, one block per row
+    fn stack<'s>(&self, lines: &mut Vec<FmtLine<'s>>, skin: &MadSkin) {
+        let table_lines: Vec<FmtLine<'s>> = lines
+            .splice(self.start..self.start + self.height, std::iter::empty())
+            .collect();
+        let has_header = matches!(table_lines.get(1), Some(FmtLine::TableRule(_)));
+        let mut rows = table_lines.into_iter().filter_map(|line| match line {
+            FmtLine::TableRow(row) => Some(row),
+            _ => None,
+        });
+        let headers = if has_header {
+            rows.next().map(|row| row.cells)
+        } else {
+            None
+        };
+        let mut new_lines = Vec::new();
+        for row in rows {
+            if !new_lines.is_empty() {
+                new_lines.push(FmtLine::HorizontalRule);
+            }
+            for (ic, cell) in row.cells.into_iter().enumerate() {
+                let mut fc = FmtComposite::new();
+                match headers.as_ref().and_then(|headers| headers.get(ic)) {
+                    Some(header) => {
+                        let mut header = header.clone();
+                        for compound in &mut header.compounds {
+                            compound.bold = true;
+                        }
+                        fc.append(header);
+                        fc.add_compound(Compound::raw_str(": "));
+                    }
+                    None => {
+                        // the label is written before the compounds
+                        fc.kind = CompositeKind::StackedCell(ic + 1);
+                        fc.visible_length = skin.visible_composite_length(fc.kind, &[]);
+                    }
+                }
+                fc.append(cell);
+                new_lines.push(FmtLine::Normal(fc));
+            }
+        }
+        lines.splice(self.start..self.start, new_lines);
+    }
     pub fn fix_columns(&mut self, lines: &mut Vec<FmtLine<'_>>, width: usize, skin: &MadSkin) {
         let mut nbcols = self.nbcols;
         if nbcols == 0 || width == 0 {
             return;
         }
+        if self.is_stacked(width, skin) {
+            self.stack(lines, skin);
+            return;
+        }
         let mut cols_removed = false;
 
         // we add the missing cells and also prepare the fitter
@@ -200,6 +278,12 @@ impl Table {
 }
 
 /// find the positions of all tables
+/// Return the label of the cells of the given column (counted
+/// from 1) of a stacked table without header, for example `"col 3"`
+pub(crate) fn column_label(column: usize) -> String {
+    format!("col {column}")
+}
+
 fn find_tables(lines: &[FmtLine<'_>]) -> Vec<Table> {
     let mut tables: Vec<Table> = Vec::new();
     let mut current: Option<Table> = None;
@@ -254,3 +338,87 @@ pub fn fix_all_tables(lines: &mut Vec<FmtLine<'_>>, width: usize, skin: &MadSkin
         tbl.fix_columns(lines, width, skin);
     }
 }
+
+#[cfg(test)]
+mod tbl_tests {
+    use {
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    static MD: &str = "|name|size|\n|-|-|\n|a|1|\n|b|2|\n";
+
+    fn render(layout: TableLayout, width: usize) -> String {
+        let mut skin = MadSkin::no_style();
+        skin.table_layout = layout;
+        skin.horizontal_rule = StyledChar::nude('-');
+        FmtText::from(&skin, MD, Some(width)).to_string()
+    }
+
+    #[test]
+    fn test_stacked_layout() {
+        let stacked = "name: a\nsize: 1\n-------\nname: b\nsize: 2\n";
+        assert_eq!(render(TableLayout::Stacked, 7), stacked);
+        // auto layout: stacked only when the columns can't fit
+        assert_eq!(render(TableLayout::Auto, 7), stacked);
+        assert_ne!(render(TableLayout::Auto, 9), stacked);
+        // without header, the columns are numbered
+        let mut skin = MadSkin::no_style();
+        skin.table_layout = TableLayout::Stacked;
+        let text = FmtText::from(&skin, "|a|1|", Some(20)).to_string();
+        assert_eq!(text, "col 1: a\ncol 2: 1\n");
+    }
+
+    #[test]
+    fn test_stacked_column_numbers() {
+        let mut skin = MadSkin::no_style();
+        skin.table_layout = TableLayout::Stacked;
+        let md: String = (1..=11).map(|i| format!("|v{i}")).collect::<String>() + "|";
+        let text = FmtText::from(&skin, &md, Some(20));
+        assert!(text.to_string().ends_with("col 10: v10\ncol 11: v11\n"));
+        // the labels are written before the compounds: only the values are searched
+        let mut search = TextSearch::new(SearchPattern::plain("col 1"));
+        search.update(&text);
+        assert_eq!(search.count(), 0);
+        let mut search = TextSearch::new(SearchPattern::plain("v1"));
+        search.update(&text);
+        assert_eq!(search.count(), 3);
+        let Some(FmtLine::Normal(fc)) = search.highlighted_line(10, &text.lines[10]) else {
+            panic!("no highlighted line");
+        };
+        let highlighted: Vec<&str> = fc
+            .compounds
+            .iter()
+            .enumerate()
+            .filter(|(idx, c)| fc.marks.iter().any(|m| m.contains(*idx, c)))
+            .map(|(_, c)| c.src)
+            .collect();
+        assert_eq!(highlighted, vec!["v1"]);
+    }
+
+    #[test]
+    fn test_search_in_stacked_layout() {
+        let mut skin = MadSkin::no_style();
+        skin.table_layout = TableLayout::Stacked;
+        let text = FmtText::from(&skin, MD, Some(7));
+        // the labels are searched, as parts of their records
+        let mut search = TextSearch::new(SearchPattern::plain("e: "));
+        search.update(&text);
+        assert_eq!(search.count(), 4);
+        let mut search = TextSearch::new(SearchPattern::plain("size: 2"));
+        search.update(&text);
+        assert_eq!(search.count(), 1);
+        assert!(search.highlighted_line(1, &text.lines[1]).is_none());
+        let Some(FmtLine::Normal(fc)) = search.highlighted_line(4, &text.lines[4]) else {
+            panic!("no highlighted line");
+        };
+        let highlighted: Vec<&str> = fc
+            .compounds
+            .iter()
+            .enumerate()
+            .filter(|(idx, c)| fc.marks.iter().any(|m| m.contains(*idx, c)))
+            .map(|(_, c)| c.src)
+            .collect();
+        assert_eq!(highlighted, vec!["size", ": ", "2"]);
+    }
+}
@@ -0,0 +1,214 @@
+use {
+    crate::{
+        crossterm::{
+            event::{
+                self,
+                Event,
+                KeyCode,
+                KeyEvent,
+                KeyModifiers,
+            },
+            terminal,
+            tty::IsTty,
+        },
+        errors::Result,
+        EscapeSequence,
+        MadSkin,
+    },
+    std::{
+        io::Write,
+        time::{
+            Duration,
+            Instant,
+        },
+    },
+};
+
+/// How long [MadSkin::default_for_terminal] waits for the
+/// terminal to answer
+pub const DEFAULT_BACKGROUND_QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Whether the background of the terminal is dark or light
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminalBackground {
+    Dark,
+    Light,
+}
+
+impl TerminalBackground {
+    /// Tell whether a background of this color is dark or light,
+    /// depending on its luminance
+    pub fn from_color(color: coolor::Rgb) -> Self {
+        if color.luma() < 0.5 {
+            Self::Dark
+        } else {
+            Self::Light
+        }
+    }
+}
+
+/// Query the terminal for its background color, with an OSC 11
+/// sequence, and wait at most `timeout` for the answer.
+///
+/// Return `None` when stdout isn't a terminal, or when the terminal
+/// didn't answer in time (not all terminals support this query).
+///
+/// This must be called before the creation of an [EventSource](crate::EventSource),
+/// which would otherwise receive the answer.
+pub fn terminal_background_color(timeout: Duration) -> Result<Option<coolor::Rgb>> {
+    let mut stdout = std::io::stdout();
+    if !stdout.is_tty() {
+        return Ok(None);
+    }
+    let raw_mode = terminal::is_raw_mode_enabled()?;
+    if !raw_mode {
+        terminal::enable_raw_mode()?;
+    }
+    let reply = query_background_color(&mut stdout, timeout);
+    if !raw_mode {
+        terminal::disable_raw_mode()?;
+    }
+    reply
+}
+
+fn query_background_color<W: Write>(w: &mut W, timeout: Duration) -> Result<Option<coolor::Rgb>> {
+    write!(w, "\u{1b}]11;?\u{1b}\\")?;
+    w.flush()?;
+    let deadline = Instant::now() + timeout;
+    let keys = std::iter::from_fn(|| loop {
+        let remaining = deadline.checked_duration_since(Instant::now())?;
+        if !event::poll(remaining).ok()? {
+            return None;
+        }
+        if let Event::Key(key) = event::read().ok()? {
+            return Some(key);
+        }
+    });
+    Ok(read_background_reply(keys))
+}
+
+/// Query the terminal for its background color and tell whether
+/// it's dark or light, `None` meaning it's unknown
+pub fn terminal_background(timeout: Duration) -> Option<TerminalBackground> {
+    terminal_background_color(timeout)
+        .ok()
+        .flatten()
+        .map(TerminalBackground::from_color)
+}
+
+/// Read the reply of the terminal to an OSC 11 query, from the key
+/// events crossterm makes of it (the reply starts with `ESC ]` and ends
+/// with `ESC \` or `BEL`).
+///
+/// Return `None` if the keys end before a valid reply.
+pub fn read_background_reply<I>(keys: I) -> Option<coolor::Rgb>
+where
+    I: IntoIterator<Item = KeyEvent>,
+{
+    let mut seq: Option<EscapeSequence> = None;
+    for key in keys {
+        let is_start = key.code == KeyCode::Char(']') && key.modifiers == KeyModifiers::ALT;
+        let is_end = (key.code == KeyCode::Char('\\') && key.modifiers == KeyModifiers::ALT)
+            || (key.code == KeyCode::Char('g') && key.modifiers == KeyModifiers::CONTROL);
+        if is_start {
+            seq = Some(EscapeSequence { keys: vec![key] });
+        } else if let Some(mut current) = seq.take() {
+            current.keys.push(key);
+            if !is_end {
+                seq = Some(current);
+            } else if let Some(color) = parse_background_reply(&current.text()) {
+                return Some(color);
+            }
+        }
+    }
+    None
+}
+
+/// Parse the text of an OSC 11 reply, like `11;rgb:1e1e/1e1e/2828`
+/// (each component having from 1 to 4 hex digits)
+fn parse_background_reply(text: &str) -> Option<coolor::Rgb> {
+    let spec = text.strip_prefix("11;")?;
+    let (_, components) = spec.split_once(':')?;
+    let mut components = components.split('/').map(|hex| {
+        if hex.is_empty() || hex.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1 << (4 * hex.len())) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    let r = components.next()??;
+    let g = components.next()??;
+    let b = components.next()??;
+    Some(coolor::Rgb { r, g, b })
+}
+
+impl MadSkin {
+    /// Build a default skin suited to the background of the terminal:
+    /// [MadSkin::default_dark] or [MadSkin::default_light] when the
+    /// terminal tells its background color, [MadSkin::default] when
+    /// it doesn't.
+    ///
+    /// This must be called before the creation of an [EventSource](crate::EventSource).
+    pub fn default_for_terminal() -> Self {
+        match terminal_background(DEFAULT_BACKGROUND_QUERY_TIMEOUT) {
+            Some(TerminalBackground::Dark) => Self::default_dark(),
+            Some(TerminalBackground::Light) => Self::default_light(),
+            None => Self::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod terminal_background_tests {
+    use {
+        crate::{
+            crossterm::event::{
+                KeyCode,
+                KeyEvent,
+                KeyModifiers,
+            },
+            *,
+        },
+        pretty_assertions::assert_eq,
+    };
+
+    /// Return the key events crossterm builds from the bytes of a reply
+    fn keys(reply: &str) -> Vec<KeyEvent> {
+        let mut keys = Vec::new();
+        let mut chars = reply.chars();
+        while let Some(c) = chars.next() {
+            keys.push(match c {
+                '\u{1b}' => KeyEvent::new(KeyCode::Char(chars.next().unwrap()), KeyModifiers::ALT),
+                '\u{7}' => KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL),
+                c if c.is_ascii_uppercase() => KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT),
+                c => KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE),
+            });
+        }
+        keys
+    }
+
+    #[test]
+    fn test_background_reply() {
+        let dark = read_background_reply(keys("\u{1b}]11;rgb:1e1e/1e1e/2828\u{1b}\\"));
+        assert_eq!(dark, Some(coolor::Rgb::new(30, 30, 40)));
+        assert_eq!(
+            dark.map(TerminalBackground::from_color),
+            Some(TerminalBackground::Dark),
+        );
+        // BEL terminated, uppercase, and preceded by unrelated keys
+        let light = read_background_reply(keys("ab\u{1b}]11;rgb:FFFF/FAFA/F0F0\u{7}"));
+        assert_eq!(light, Some(coolor::Rgb::new(255, 250, 240)));
+        assert_eq!(
+            light.map(TerminalBackground::from_color),
+            Some(TerminalBackground::Light),
+        );
+        // short components
+        let short = read_background_reply(keys("\u{1b}]11;rgb:f/80/000\u{1b}\\"));
+        assert_eq!(short, Some(coolor::Rgb::new(255, 128, 0)));
+        // incomplete or missing replies
+        assert_eq!(read_background_reply(keys("\u{1b}]11;rgb:1e1e/1e1e")), None);
+        assert_eq!(read_background_reply(keys("\u{1b}]11;rgb:zz/1e/1e\u{1b}\\")), None);
+        assert_eq!(read_background_reply(Vec::new()), None);
+    }
+}
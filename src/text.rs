@@ -1,14 +1,21 @@
 use {
     crate::{
+        admonition,
         code,
         fit::wrap,
         line::FmtLine,
+        line_break,
+        link::{
+            self,
+            LinkRendering,
+        },
+        list_numbering,
         skin::MadSkin,
         tbl,
     },
     minimad::{
-        parse_text,
-        Options,
+        parser::LineParser,
+        Line,
         Text,
     },
     std::fmt,
@@ -39,7 +46,7 @@ impl<'k, 's> FmtText<'k, 's> {
     /// This can be called directly or using one of the skin helper
     /// method.
     pub fn from(skin: &'k MadSkin, src: &'s str, width: Option<usize>) -> FmtText<'k, 's> {
-        let mt = parse_text(src, Options::default());
+        let mt = parse_keeping_fences(src);
         Self::from_text(skin, mt, width)
     }
     /// build a text as raw (with no markdown interpretation)
@@ -54,19 +61,42 @@ impl<'k, 's> FmtText<'k, 's> {
         mut text: Text<'s>,
         width: Option<usize>,
     ) -> FmtText<'k, 's> {
-        let mut lines = text
-            .lines
-            .drain(..)
-            .map(|mline| FmtLine::from(mline, skin))
-            .collect();
+        let mut lines = Vec::new();
+        let mut fence: Option<code::CodeFence<'s>> = None;
+        let mut fences = Vec::new();
+        for mline in text.lines.drain(..) {
+            if let Line::CodeFence(composite) = &mline {
+                match fence.take() {
+                    Some(fence) => {
+                        fence.highlight(&mut lines, skin);
+                        fences.push(fence);
+                    }
+                    None => fence = Some(code::CodeFence::new(composite, lines.len())),
+                }
+                continue;
+            }
+            lines.push(FmtLine::from(mline, skin));
+        }
+        if let Some(fence) = fence {
+            // the block wasn't closed
+            fence.highlight(&mut lines, skin);
+            fences.push(fence);
+        }
+        if skin.link_rendering == LinkRendering::Footnotes {
+            link::add_footnotes(&mut lines, skin);
+        }
+        list_numbering::number_ordered_lists(&mut lines, skin);
+        admonition::find_admonitions(&mut lines, skin);
+        code::frame_blocks(&mut lines, &fences, width, skin);
         tbl::fix_all_tables(&mut lines, width.unwrap_or(usize::MAX), skin);
-        code::justify_blocks(&mut lines);
         if let Some(width) = width {
             if width >= 3 {
                 lines =
                     wrap::hard_wrap_lines(lines, width, skin).expect("width should be wide enough");
             }
         }
+        line_break::remove_all_soft_hyphens(&mut lines, skin);
+        code::justify_blocks(&mut lines);
         FmtText { skin, lines, width }
     }
     /// set the width to render the text to.
@@ -90,6 +120,27 @@ impl<'k, 's> FmtText<'k, 's> {
     }
 }
 
+/// parse a markdown text like minimad's `parse_text` with default
+/// options, but keep the code fences, as their language is needed
+/// for highlighting
+fn parse_keeping_fences(src: &str) -> Text<'_> {
+    let mut lines = Vec::new();
+    let mut between_fences = false;
+    for md_line in src.lines() {
+        let parser = LineParser::from(md_line);
+        let line = if between_fences {
+            parser.as_code()
+        } else {
+            parser.line()
+        };
+        if let Line::CodeFence(..) = line {
+            between_fences = !between_fences;
+        }
+        lines.push(line);
+    }
+    Text { lines }
+}
+
 impl fmt::Display for FmtText<'_, '_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for line in &self.lines {
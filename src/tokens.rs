@@ -1,51 +1,108 @@
 use {
-    crate::*,
+    crate::{
+        compound_range::SourcedCompound,
+        line_break::{
+            self,
+            SOFT_HYPHEN,
+        },
+        *,
+    },
     minimad::*,
-    unicode_width::UnicodeWidthChar,
+    unicode_segmentation::UnicodeSegmentation,
+    unicode_width::UnicodeWidthStr,
 };
 
 #[derive(Debug)]
 pub(crate) struct Token<'s> {
     pub compound: Compound<'s>,
     pub blank: bool,
+    /// a place where the line may be broken, with a hyphen added: a
+    /// soft hyphen or a hyphenation point (then the token is empty)
+    pub hyphen: bool,
     pub width: usize,
+    /// index of the compound in the tokenized slice
+    pub compound_idx: usize,
     pub start_in_compound: usize,
     pub end_in_compound: usize,
 }
 
 impl<'s> Token<'s> {
-    pub fn to_compound(&self) -> Compound<'s> {
-        let mut compound = self.compound.clone();
-        compound.set_str(&self.compound.src[self.start_in_compound..self.end_in_compound]);
-        compound
+    /// Return the token as a compound knowing it's a part of the
+    /// compound of index `compound_idx`
+    pub fn to_sourced_compound(&self) -> SourcedCompound<'s> {
+        SourcedCompound::new(self.compound_idx, self.compound.clone())
+            .sub(self.start_in_compound, self.end_in_compound)
+    }
+    /// Tell whether the token ends its compound
+    pub fn ends_compound(&self) -> bool {
+        self.end_in_compound == self.compound.src.len()
     }
 }
 
 /// Cut a composite into token, each one being either only spaces or without space, and
-/// each one from one compound
+/// each one from one compound, never cutting an extended grapheme cluster.
+///
+/// Outside of code, non blank tokens are also cut where the line may be broken (for
+/// example between ideographs), and hyphenation points are hyphen tokens.
 pub(crate) fn tokenize<'s, 'c>(
     compounds: &'c [Compound<'s>],
     max_token_width: usize,
+    hyphenator: Option<&dyn Hyphenator>,
 ) -> Vec<Token<'s>> {
     let mut tokens: Vec<Token<'s>> = Vec::new();
-    for compound in compounds {
+    for (compound_idx, compound) in compounds.iter().enumerate() {
+        let hyphenation_points = match hyphenator {
+            Some(hyphenator) if !compound.code => {
+                line_break::hyphenation_points(hyphenator, compound.src)
+            }
+            _ => Vec::new(),
+        };
         let mut token: Option<Token> = None;
-        for (idx, char) in compound.src.char_indices() {
+        let mut previous: (Option<char>, Option<char>) = (None, None);
+        for (idx, grapheme) in compound.src.grapheme_indices(true) {
+            let char = grapheme.chars().next().unwrap_or(' ');
+            let soft_hyphen = char == SOFT_HYPHEN && !compound.code;
+            if soft_hyphen || hyphenation_points.contains(&idx) {
+                if let Some(token) = token.take() {
+                    tokens.push(token);
+                }
+                tokens.push(Token {
+                    compound: compound.clone(),
+                    blank: false,
+                    hyphen: true,
+                    width: 0,
+                    compound_idx,
+                    start_in_compound: idx,
+                    end_in_compound: if soft_hyphen { idx + grapheme.len() } else { idx },
+                });
+                if soft_hyphen {
+                    previous = (None, None);
+                    continue;
+                }
+            }
             let blank = char.is_whitespace() && !compound.code;
-            let char_width = char.width().unwrap_or(0);
+            let grapheme_width = grapheme.width();
+            let breakable = !compound.code
+                && previous
+                    .1
+                    .map_or(false, |p| line_break::is_break_opportunity(previous.0, p, char));
+            previous = (previous.1, Some(char));
             if let Some(token) = token.as_mut() {
-                if token.blank == blank && token.width + char_width <= max_token_width {
-                    token.width += char_width;
-                    token.end_in_compound += char.len_utf8();
+                let fits = token.width + grapheme_width <= max_token_width;
+                if token.blank == blank && !breakable && fits {
+                    token.width += grapheme_width;
+                    token.end_in_compound += grapheme.len();
                     continue;
                 }
             }
             let new_token = Token {
                 compound: compound.clone(),
                 blank,
-                width: char_width,
+                hyphen: false,
+                width: grapheme_width,
+                compound_idx,
                 start_in_compound: idx,
-                end_in_compound: idx + char.len_utf8(),
+                end_in_compound: idx + grapheme.len(),
             };
             if let Some(token) = token.replace(new_token) {
                 tokens.push(token);
@@ -606,7 +606,6 @@ impl InputField {
             .content
             .lines()
             .iter()
-            .map(|line| &line.chars)
             .enumerate()
             .skip(self.scroll.y);
 
@@ -614,8 +613,8 @@ impl InputField {
 
         for j in 0..self.area.height {
             queue!(w, cursor::MoveTo(self.area.left, j + self.area.top))?;
-            if let Some((y, chars)) = numbered_lines.next() {
-                let cursor_at_end = self.focused && y == pos.y && pos.x == chars.len();
+            if let Some((y, line)) = numbered_lines.next() {
+                let cursor_at_end = self.focused && y == pos.y && pos.x == line.chars.len();
                 let mut width_to_skip = self.scroll.x;
                 let mut skipped_width = 0;
                 let mut displayed_width = 0;
@@ -626,16 +625,20 @@ impl InputField {
                     width_to_skip += 1;
                     width -= 1;
                 }
-                for (i, c) in chars.iter().enumerate() {
-                    let c = if self.password_mode { '*' } else { *c };
-                    let char_width = InputFieldContent::char_width(c);
+                // we render grapheme clusters, so that the cursor, the selection
+                // or the cropping never break a visible character
+                let graphemes = line.graphemes();
+                for (gi, (i, g)) in graphemes.iter().enumerate() {
+                    let i = *i;
+                    let g = if self.password_mode { "*" } else { g.as_str() };
+                    let char_width = InputFieldContent::grapheme_width(g);
                     if skipped_width < width_to_skip {
                         // char hidden by scroll on x
                         skipped_width += char_width;
                         continue;
                     }
                     if displayed_width + char_width >= width {
-                        let is_last = i == chars.len() - 1;
+                        let is_last = gi == graphemes.len() - 1;
                         if !is_last || displayed_width + char_width > width {
                             if self.focused && selection.contains(i, y) {
                                 self.cursor_style.queue(w, fit::ELLIPSIS)?;
@@ -652,12 +655,12 @@ impl InputField {
                         }
                     }
                     if self.focused && selection.contains(i, y) {
-                        self.cursor_style.queue(w, c)?;
+                        self.cursor_style.queue(w, g)?;
                         // set terminal cursor position
                         terminal_cursor_pos =
                             Some((self.area.left + displayed_width as u16, self.area.top + j));
                     } else {
-                        normal_style.queue(w, c)?;
+                        normal_style.queue(w, g)?;
                     }
                     displayed_width += char_width;
                     if displayed_width >= width {
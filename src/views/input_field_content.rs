@@ -5,7 +5,14 @@ use {
     },
     crate::TAB_REPLACEMENT,
     std::fmt,
-    unicode_width::UnicodeWidthChar,
+    unicode_segmentation::{
+        GraphemeCursor,
+        GraphemeIncomplete,
+    },
+    unicode_width::{
+        UnicodeWidthChar,
+        UnicodeWidthStr,
+    },
 };
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -101,27 +108,130 @@ impl fmt::Display for InputFieldContent {
 }
 
 impl Line {
+    /// Return the extended grapheme clusters of the line (an emoji
+    /// sequence, a letter with its combining accents, etc.), with
+    /// the index of their first char
+    pub(crate) fn graphemes(&self) -> Vec<(usize, String)> {
+        let mut graphemes = Vec::new();
+        self.for_each_grapheme(|start, g| {
+            graphemes.push((start, g.to_string()));
+            true
+        });
+        graphemes
+    }
+    /// Tell whether a grapheme cluster starts at the char of index `idx`,
+    /// `byte_idx` being its index in the UTF-8 encoding of the line,
+    /// whose length is `len_bytes`
+    fn is_boundary_at(&self, idx: usize, byte_idx: usize, len_bytes: usize) -> bool {
+        if idx == 0 || idx >= self.chars.len() {
+            return true;
+        }
+        let mut cursor = GraphemeCursor::new(byte_idx, len_bytes, true);
+        let mut buf = [0; 4];
+        let chunk = self.chars[idx].encode_utf8(&mut buf);
+        // the preceding chars are given, one by one, when asked for
+        let mut ctx_idx = idx;
+        let mut ctx_byte_idx = byte_idx;
+        loop {
+            match cursor.is_boundary(chunk, byte_idx) {
+                Ok(is_boundary) => return is_boundary,
+                Err(GraphemeIncomplete::PreContext(_)) if ctx_idx > 0 => {
+                    ctx_idx -= 1;
+                    let c = self.chars[ctx_idx];
+                    ctx_byte_idx -= c.len_utf8();
+                    let mut buf = [0; 4];
+                    cursor.provide_context(c.encode_utf8(&mut buf), ctx_byte_idx);
+                }
+                _ => return true,
+            }
+        }
+    }
+    fn len_bytes(chars: &[char]) -> usize {
+        chars.iter().map(|c| c.len_utf8()).sum()
+    }
+    /// Call `f` with the index of the first char and the string of every
+    /// grapheme cluster, until it returns false
+    fn for_each_grapheme<F>(&self, mut f: F)
+    where
+        F: FnMut(usize, &str) -> bool,
+    {
+        let len_bytes = Self::len_bytes(&self.chars);
+        let mut cluster = String::new();
+        let mut start = 0;
+        let mut byte_idx = 0;
+        for idx in 1..=self.chars.len() {
+            byte_idx += self.chars[idx - 1].len_utf8();
+            if self.is_boundary_at(idx, byte_idx, len_bytes) {
+                cluster.clear();
+                cluster.extend(&self.chars[start..idx]);
+                if !f(start, &cluster) {
+                    return;
+                }
+                start = idx;
+            }
+        }
+    }
+    /// Return the index of the first char of the grapheme cluster
+    /// containing the char at the given index
+    pub fn grapheme_start(&self, idx: usize) -> usize {
+        if idx >= self.chars.len() {
+            return self.chars.len();
+        }
+        let len_bytes = Self::len_bytes(&self.chars);
+        let mut idx = idx;
+        let mut byte_idx = Self::len_bytes(&self.chars[..idx]);
+        while !self.is_boundary_at(idx, byte_idx, len_bytes) {
+            idx -= 1;
+            byte_idx -= self.chars[idx].len_utf8();
+        }
+        idx
+    }
+    /// Return the index following the last char of the grapheme
+    /// cluster containing the char at the given index
+    pub fn grapheme_end(&self, idx: usize) -> usize {
+        if idx >= self.chars.len() {
+            return self.chars.len();
+        }
+        let len_bytes = Self::len_bytes(&self.chars);
+        let mut idx = idx + 1;
+        let mut byte_idx = Self::len_bytes(&self.chars[..idx]);
+        while !self.is_boundary_at(idx, byte_idx, len_bytes) {
+            byte_idx += self.chars[idx].len_utf8();
+            idx += 1;
+        }
+        idx
+    }
     pub fn col_to_char_idx(&self, col: usize) -> Option<usize> {
         let mut sum_widths = 0;
-        for (idx, &c) in self.chars.iter().enumerate() {
+        let mut char_idx = None;
+        self.for_each_grapheme(|idx, g| {
             if col <= sum_widths {
-                return Some(idx);
+                char_idx = Some(idx);
+                return false;
             }
-            sum_widths += InputFieldContent::char_width(c);
-        }
-        None
+            sum_widths += InputFieldContent::grapheme_width(g);
+            true
+        });
+        char_idx
     }
     pub fn char_idx_to_col(&self, idx: usize) -> usize {
-        self.chars[0..idx]
-            .iter()
-            .map(|&c| InputFieldContent::char_width(c))
-            .sum()
+        let mut col = 0;
+        self.for_each_grapheme(|start, g| {
+            if start >= idx {
+                return false;
+            }
+            col += InputFieldContent::grapheme_width(g);
+            true
+        });
+        col
     }
     pub fn width(&self) -> usize {
-        self.chars
-            .iter()
-            .map(|&c| InputFieldContent::char_width(c))
-            .sum()
+        let mut width = 0;
+        self.for_each_grapheme(|_, g| {
+            width += InputFieldContent::grapheme_width(g);
+            true
+        });
+        width
     }
 }
 
@@ -160,7 +270,8 @@ impl InputFieldContent {
         if pos.y >= self.lines.len() {
             self.end()
         } else {
-            pos.x = pos.x.min(self.lines[pos.y].chars.len());
+            let line = &self.lines[pos.y];
+            pos.x = line.grapheme_start(pos.x.min(line.chars.len()));
             pos
         }
     }
@@ -177,9 +288,10 @@ impl InputFieldContent {
         if sel_tail.y >= self.lines.len() {
             self.selection_tail = Some(self.end());
         } else {
+            let line = &self.lines[sel_tail.y];
             self.selection_tail = Some(Pos {
                 y: sel_tail.y,
-                x: sel_tail.x.min(self.lines[self.pos.y].chars.len()),
+                x: line.grapheme_start(sel_tail.x.min(line.chars.len())),
             });
         }
     }
@@ -218,7 +330,12 @@ impl InputFieldContent {
     /// return an iterator over the characters of the
     /// selection (including some newline chars maybe)
     pub fn selection_chars(&self) -> Chars<'_> {
-        let Range { min, max } = self.selection();
+        let Range { min, mut max } = self.selection();
+        // the last grapheme cluster is selected as a whole
+        let line = &self.lines[max.y];
+        if max.x < line.chars.len() {
+            max.x = line.grapheme_end(max.x) - 1;
+        }
         Chars {
             content: self,
             pos: min,
@@ -333,13 +450,13 @@ impl InputFieldContent {
         self.clear();
         self.insert_str(s);
     }
-    /// Remove the char left of the cursor, if any.
+    /// Remove the char (the grapheme cluster) left of the cursor, if any.
     pub fn del_char_left(&mut self) -> bool {
         if self.pos.x > 0 {
-            self.pos.x -= 1;
-            if !self.lines[self.pos.y].chars.is_empty() {
-                self.lines[self.pos.y].chars.remove(self.pos.x);
-            }
+            let line = &mut self.lines[self.pos.y];
+            let start = line.grapheme_start(self.pos.x - 1);
+            line.chars.drain(start..self.pos.x);
+            self.pos.x = start;
             self.fix_selection();
             true
         } else if self.pos.y > 0 && self.lines.len() > 1 {
@@ -392,7 +509,7 @@ impl InputFieldContent {
         true
     }
 
-    /// Remove the char at cursor position, if any.
+    /// Remove the char (the grapheme cluster) at cursor position, if any.
     ///
     /// Cursor position is unchanged
     pub fn del_char_below(&mut self) -> bool {
@@ -406,7 +523,9 @@ impl InputFieldContent {
                 false
             }
         } else if self.pos.x < line_len {
-            self.lines[self.pos.y].chars.remove(self.pos.x);
+            let line = &mut self.lines[self.pos.y];
+            let end = line.grapheme_end(self.pos.x);
+            line.chars.drain(self.pos.x..end);
             self.fix_selection();
             true
         } else if self.lines.len() > self.pos.y + 1 {
@@ -436,7 +555,8 @@ impl InputFieldContent {
                     self.lines[min.y].chars.drain(min.x..);
                 }
             } else {
-                self.lines[min.y].chars.drain(min.x..max.x + 1);
+                let end = self.lines[min.y].grapheme_end(max.x);
+                self.lines[min.y].chars.drain(min.x..end);
             }
         } else {
             let min_y = if min.x > 0 {
@@ -505,7 +625,7 @@ impl InputFieldContent {
     /// if it's a the end of a non-last line)
     pub fn move_right(&mut self) -> bool {
         if self.pos.x < self.lines[self.pos.y].chars.len() {
-            self.pos.x += 1;
+            self.pos.x = self.lines[self.pos.y].grapheme_end(self.pos.x);
             true
         } else if self.pos.y < self.lines.len() - 1 {
             self.pos.y += 1;
@@ -551,7 +671,7 @@ impl InputFieldContent {
     }
     pub fn move_left(&mut self) -> bool {
         if self.pos.x > 0 {
-            self.pos.x -= 1;
+            self.pos.x = self.lines[self.pos.y].grapheme_start(self.pos.x - 1);
             true
         } else if self.pos.y > 0 {
             self.pos.y -= 1;
@@ -605,6 +725,7 @@ impl InputFieldContent {
                     break;
                 }
             }
+            self.fix_pos();
             true
         } else {
             false
@@ -619,6 +740,7 @@ impl InputFieldContent {
                     break;
                 }
             }
+            self.fix_pos();
             true
         } else {
             false
@@ -634,6 +756,11 @@ impl InputFieldContent {
                     break;
                 }
             }
+            // we don't leave a part of a grapheme cluster
+            let line = &mut self.lines[self.pos.y];
+            let start = line.grapheme_start(self.pos.x);
+            line.chars.drain(start..self.pos.x);
+            self.pos.x = start;
             self.fix_selection();
             true
         } else {
@@ -679,6 +806,16 @@ impl InputFieldContent {
             _ => UnicodeWidthChar::width(c).unwrap_or(0),
         }
     }
+
+    /// Return the number of columns taken by an extended grapheme
+    /// cluster (as the width of a sequence of chars may be different
+    /// from the sum of their widths)
+    pub fn grapheme_width(g: &str) -> usize {
+        match g {
+            "\t" => TAB_REPLACEMENT.len(),
+            _ => g.width(),
+        }
+    }
 }
 
 #[test]
@@ -774,4 +911,39 @@ mod input_content_edit_monoline_tests {
         con.del_char_left();
         con.del_selection();
     }
+    /// test that the cursor moves and deletions never break a grapheme cluster
+    #[test]
+    fn test_grapheme_clusters() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let mut con = InputFieldContent::from(format!("a{family}e\u{301}🇫🇷"));
+        assert_eq!(con.current_line().chars.len(), 10);
+        assert_eq!(con.current_line().width(), 6);
+        con.move_left();
+        assert_eq!(con.cursor_pos().x, 8);
+        assert_eq!(con.current_line().char_idx_to_col(8), 4);
+        con.move_left();
+        assert_eq!(con.cursor_pos().x, 6);
+        con.move_left();
+        assert_eq!(con.cursor_pos().x, 1);
+        assert_eq!(con.current_line().col_to_char_idx(3), Some(6));
+        con.set_cursor_pos(Pos::new(3, 0));
+        assert_eq!(con.cursor_pos().x, 1);
+        con.make_selection();
+        con.move_right();
+        assert_eq!(con.cursor_pos().x, 6);
+        assert_eq!(con.selection_string(), format!("{family}e\u{301}"));
+        con.unselect();
+        con.del_char_below();
+        assert!(con.is_str(&format!("a{family}🇫🇷")));
+        con.del_char_left();
+        assert!(con.is_str("a🇫🇷"));
+        assert_eq!(con.cursor_pos().x, 1);
+        // consecutive flags, made of pairs of regional indicators
+        let flags = InputFieldContent::from("🇫🇷🇩🇪🇮🇹");
+        let line = flags.current_line();
+        assert_eq!(line.grapheme_start(3), 2);
+        assert_eq!(line.grapheme_start(4), 4);
+        assert_eq!(line.grapheme_end(2), 4);
+        assert_eq!(line.width(), 6);
+    }
 }
@@ -4,10 +4,7 @@ use {
         crossterm::{
             cursor::MoveTo,
             queue,
-            style::{
-                Color,
-                SetBackgroundColor,
-            },
+            style::Color,
             terminal::{
                 Clear,
                 ClearType,
@@ -304,7 +301,7 @@ impl<'t, T> ListView<'t, T> {
                     for (col_idx, col) in self.columns.iter().enumerate() {
                         if col_idx != 0 {
                             if selected {
-                                queue!(w, SetBackgroundColor(self.selection_background))?;
+                                CompoundStyle::with_bg(self.selection_background).queue_bg(w)?;
                             }
                             vbar.queue(w)?;
                         }
@@ -429,7 +426,7 @@ impl<'t, T> ListView<'t, T> {
             if sel <= self.scroll {
                 self.scroll = sel.saturating_sub(2);
             } else if sel + 1 >= self.scroll + tbody_height {
-                self.scroll = sel - tbody_height + 2;
+                self.scroll = (sel + 2).saturating_sub(tbody_height);
             }
         }
     }
@@ -1,10 +1,21 @@
 use {
     crate::{
         area::Area,
-        crossterm::event::KeyEvent,
+        crossterm::event::{
+            Event,
+            KeyEvent,
+        },
         errors::Result,
+        search::{
+            SearchPattern,
+            TextSearch,
+        },
         skin::MadSkin,
-        views::TextView,
+        views::{
+            TextSelection,
+            TextView,
+        },
+        TimedEvent,
     },
     crokey::KeyCombination,
     std::io::Write,
@@ -19,6 +30,8 @@ pub struct MadView {
     area: Area,
     pub skin: MadSkin,
     pub scroll: usize,
+    search: Option<TextSearch>,
+    selection: TextSelection,
 }
 
 impl MadView {
@@ -29,6 +42,8 @@ impl MadView {
             area,
             skin,
             scroll: 0,
+            search: None,
+            selection: TextSelection::new(),
         }
     }
     /// render the markdown in the area, taking the scroll into
@@ -40,6 +55,8 @@ impl MadView {
         let text = self.skin.area_text(&self.markdown, &self.area);
         let mut text_view = TextView::from(&self.area, &text);
         text_view.scroll = self.scroll;
+        text_view.search = self.search.as_ref();
+        text_view.selection = Some(&self.selection);
         text_view.write_on(w)?;
         Ok(())
     }
@@ -51,11 +68,96 @@ impl MadView {
         }
         if area.width != self.area.width {
             self.scroll = 0; //TODO improve
+            self.selection.clear();
         }
         self.area.left = area.left;
         self.area.top = area.top;
         self.area.height = area.height;
         self.area.width = area.width;
+        if let Some(search) = self.search.as_mut() {
+            search.update(&self.skin.area_text(&self.markdown, &self.area));
+        }
+    }
+    /// Apply a mouse event to the selection, or a key to the scroll.
+    ///
+    /// Return true when the event led to a change.
+    pub fn apply_timed_event(&mut self, event: &TimedEvent) -> bool {
+        if let Some(key) = event.key_combination {
+            return self.apply_key_combination(key);
+        }
+        if !matches!(event.event, Event::Mouse(_)) {
+            return false;
+        }
+        let text = self.skin.area_text(&self.markdown, &self.area);
+        let mut text_view = TextView::from(&self.area, &text);
+        text_view.scroll = self.scroll;
+        self.selection.apply_timed_event(&text_view, event)
+    }
+    /// Return the selection, made with the mouse
+    pub fn selection(&self) -> &TextSelection {
+        &self.selection
+    }
+    /// Return the selected source text, or None if nothing is selected
+    pub fn selected_text(&self) -> Option<String> {
+        let text = self.skin.area_text(&self.markdown, &self.area);
+        let mut text_view = TextView::from(&self.area, &text);
+        text_view.scroll = self.scroll;
+        self.selection.text(&text_view)
+    }
+    /// Set or remove the search whose matches are highlighted.
+    ///
+    /// The current match is the first one in or after the visible
+    /// lines, and the view scrolls to make it visible.
+    pub fn set_search(&mut self, pattern: Option<SearchPattern>) {
+        let Some(pattern) = pattern else {
+            self.search = None;
+            return;
+        };
+        let mut search = TextSearch::new(pattern);
+        let text = self.skin.area_text(&self.markdown, &self.area);
+        search.update(&text);
+        search.select_from_line(self.scroll);
+        let mut text_view = TextView::from(&self.area, &text);
+        text_view.scroll = self.scroll;
+        text_view.show_search_match(&search);
+        self.scroll = text_view.scroll;
+        self.search = Some(search);
+    }
+    /// Return the current search, with its matches, for example
+    /// to display their count
+    pub fn search(&self) -> Option<&TextSearch> {
+        self.search.as_ref()
+    }
+    /// Make the next match of the search current, and scroll to it.
+    ///
+    /// Return false when there's no match.
+    pub fn search_next(&mut self) -> bool {
+        self.move_in_search(true)
+    }
+    /// Make the previous match of the search current, and scroll to it.
+    ///
+    /// Return false when there's no match.
+    pub fn search_previous(&mut self) -> bool {
+        self.move_in_search(false)
+    }
+    fn move_in_search(&mut self, forward: bool) -> bool {
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+        let found = if forward {
+            search.select_next()
+        } else {
+            search.select_previous()
+        };
+        if found.is_none() {
+            return false;
+        }
+        let text = self.skin.area_text(&self.markdown, &self.area);
+        let mut text_view = TextView::from(&self.area, &text);
+        text_view.scroll = self.scroll;
+        text_view.show_search_match(search);
+        self.scroll = text_view.scroll;
+        true
     }
     /// set the scroll amount.
     /// lines_count can be negative
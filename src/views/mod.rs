@@ -4,6 +4,7 @@ mod list_view;
 mod mad_view;
 mod pos;
 mod progress;
+mod text_selection;
 mod text_view;
 
 pub use {
@@ -20,5 +21,9 @@ pub use {
         Range,
     },
     progress::ProgressBar,
+    text_selection::{
+        SelectionMode,
+        TextSelection,
+    },
     text_view::TextView,
 };
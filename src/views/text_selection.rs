@@ -0,0 +1,447 @@
+use {
+    super::{
+        Pos,
+        TextView,
+    },
+    crate::{
+        crossterm::event::{
+            Event,
+            MouseButton,
+            MouseEvent,
+            MouseEventKind,
+        },
+        mark::{
+            self,
+            MarkKind,
+        },
+        FmtLine,
+        MadSkin,
+        TimedEvent,
+    },
+    std::time::{
+        Duration,
+        Instant,
+    },
+    unicode_width::UnicodeWidthChar,
+};
+
+const TRIPLE_CLICK_MAX_DURATION: Duration = Duration::from_millis(700);
+
+/// How the ends of a selection are extended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectionMode {
+    #[default]
+    Chars,
+    Words,
+    Lines,
+}
+
+/// A selection of the text displayed in a [TextView] or a
+/// [MadView](crate::MadView), made with the mouse:
+/// - drag to select chars
+/// - double-click to select a word (then drag to select more)
+/// - triple-click to select a line
+///
+/// The selected text is made of the source chars only: bullets,
+/// margins and table borders are excluded.
+///
+/// As the text view itself, the selection is expressed in lines of
+/// the formatted text, so it must be cleared when the text is rebuilt
+/// with another width.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextSelection {
+    /// where the selection started (x being the column in the area,
+    /// y the line in the text), None if there's no selection
+    anchor: Option<Pos>,
+    /// the other end of the selection
+    head: Pos,
+    mode: SelectionMode,
+    /// time and position of the last double click, to detect triple clicks
+    last_double_click: Option<(Instant, Pos)>,
+}
+
+/// A char of the content of a displayed line
+#[derive(Debug, Clone, Copy)]
+struct LineChar {
+    /// column in the line
+    x: usize,
+    width: usize,
+    /// index of the composite in the line
+    cell: usize,
+    /// byte index in the concatenated compounds of the composite
+    offset: usize,
+    ch: char,
+}
+
+impl LineChar {
+    fn last_x(self) -> usize {
+        self.x + self.width.max(1) - 1
+    }
+}
+
+/// return the chars of the composites of a line, with their positions
+fn line_chars(skin: &MadSkin, line: &FmtLine<'_>, width: usize) -> Vec<LineChar> {
+    let starts = skin.fmt_line_content_starts(line, Some(width));
+    let mut chars = Vec::new();
+    for (cell, (fc, start)) in mark::line_composites(line)
+        .into_iter()
+        .zip(starts)
+        .enumerate()
+    {
+        let mut x = start;
+        let mut offset = 0;
+        for (idx, compound) in fc.compounds.iter().enumerate() {
+            for (i, ch) in compound.src.char_indices() {
+                let width = ch.width().unwrap_or(0);
+                chars.push(LineChar {
+                    x,
+                    width,
+                    cell,
+                    offset: offset + i,
+                    ch,
+                });
+                x += width;
+            }
+            x += fc.width_after(idx);
+            offset += compound.src.len();
+        }
+    }
+    chars
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// return the indexes of the first and last chars of the word at x
+fn word_around(chars: &[LineChar], x: usize) -> Option<(usize, usize)> {
+    let idx = chars.iter().position(|c| c.x <= x && x <= c.last_x())?;
+    if !is_word_char(chars[idx].ch) {
+        return None;
+    }
+    let same_word = |a: &LineChar, b: &LineChar| a.cell == b.cell && is_word_char(b.ch);
+    let mut start = idx;
+    while start > 0 && same_word(&chars[start], &chars[start - 1]) {
+        start -= 1;
+    }
+    let mut end = idx;
+    while end + 1 < chars.len() && same_word(&chars[end], &chars[end + 1]) {
+        end += 1;
+    }
+    Some((start, end))
+}
+
+impl TextSelection {
+    pub const fn new() -> Self {
+        Self {
+            anchor: None,
+            head: Pos::new(0, 0),
+            mode: SelectionMode::Chars,
+            last_double_click: None,
+        }
+    }
+    pub fn clear(&mut self) {
+        self.anchor = None;
+    }
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+    /// Tell whether nothing is selected (a simple click doesn't select anything)
+    pub fn is_empty(&self) -> bool {
+        match self.anchor {
+            Some(anchor) => self.mode == SelectionMode::Chars && anchor == self.head,
+            None => true,
+        }
+    }
+    /// Select the chars between two positions (x being the column in the
+    /// area and y the line in the text), both included
+    pub fn select(&mut self, start: Pos, end: Pos, mode: SelectionMode) {
+        self.anchor = Some(start);
+        self.head = end;
+        self.mode = mode;
+    }
+    /// Apply a mouse event: a click starts a selection, a double click
+    /// selects a word, a triple click a line, and dragging extends
+    /// the selection.
+    ///
+    /// Return true when the event was used.
+    pub fn apply_timed_event(&mut self, view: &TextView<'_, '_>, event: &TimedEvent) -> bool {
+        match event.event {
+            Event::Mouse(mouse_event) => {
+                self.apply_mouse_event_at(view, mouse_event, event.double_click, event.time)
+            }
+            _ => false,
+        }
+    }
+    /// Apply a mouse event, see [TextSelection::apply_timed_event]
+    pub fn apply_mouse_event(
+        &mut self,
+        view: &TextView<'_, '_>,
+        mouse_event: MouseEvent,
+        is_double_click: bool,
+    ) -> bool {
+        self.apply_mouse_event_at(view, mouse_event, is_double_click, Instant::now())
+    }
+    fn apply_mouse_event_at(
+        &mut self,
+        view: &TextView<'_, '_>,
+        mouse_event: MouseEvent,
+        is_double_click: bool,
+        time: Instant,
+    ) -> bool {
+        let MouseEvent {
+            kind, column, row, ..
+        } = mouse_event;
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(pos) = view.text_pos(column, row) else {
+                    return false;
+                };
+                let is_triple_click = self.last_double_click.map_or(false, |(t, p)| {
+                    p == pos && time.saturating_duration_since(t) < TRIPLE_CLICK_MAX_DURATION
+                });
+                self.mode = if is_triple_click {
+                    self.last_double_click = None;
+                    SelectionMode::Lines
+                } else if is_double_click {
+                    self.last_double_click = Some((time, pos));
+                    SelectionMode::Words
+                } else {
+                    SelectionMode::Chars
+                };
+                self.anchor = Some(pos);
+                self.head = pos;
+                true
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.anchor.is_some() => {
+                self.head = view.clamped_text_pos(column, row);
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Return the first and last selected positions (both included),
+    /// extended according to the mode
+    fn bounds(&self, view: &TextView<'_, '_>) -> Option<(Pos, Pos)> {
+        if self.is_empty() {
+            return None;
+        }
+        let anchor = self.anchor?;
+        let (mut start, mut end) = if anchor <= self.head {
+            (anchor, self.head)
+        } else {
+            (self.head, anchor)
+        };
+        match self.mode {
+            SelectionMode::Chars => {}
+            SelectionMode::Words => {
+                let chars = view.line_chars(start.y);
+                if let Some((first, _)) = word_around(&chars, start.x) {
+                    start.x = chars[first].x;
+                }
+                let chars = view.line_chars(end.y);
+                if let Some((_, last)) = word_around(&chars, end.x) {
+                    end.x = chars[last].last_x();
+                }
+            }
+            SelectionMode::Lines => {
+                start.x = 0;
+                end.x = usize::MAX;
+            }
+        }
+        Some((start, end))
+    }
+    /// Return the selected chars of a line
+    fn selected_chars(
+        view: &TextView<'_, '_>,
+        (start, end): (Pos, Pos),
+        line_idx: usize,
+    ) -> Vec<LineChar> {
+        if line_idx < start.y || line_idx > end.y {
+            return Vec::new();
+        }
+        let min_x = if line_idx == start.y { start.x } else { 0 };
+        let max_x = if line_idx == end.y { end.x } else { usize::MAX };
+        let mut chars = view.line_chars(line_idx);
+        chars.retain(|c| c.x <= max_x && c.last_x() >= min_x);
+        chars
+    }
+    /// Return the selected source text, or None if nothing is selected.
+    ///
+    /// Lines are separated with a newline, unless they were broken by
+    /// wrapping, and table cells with a tab.
+    pub fn text(&self, view: &TextView<'_, '_>) -> Option<String> {
+        let bounds = self.bounds(view)?;
+        let (start, end) = bounds;
+        let end_y = end.y.min(view.content_height().saturating_sub(1));
+        let mut s = String::new();
+        for line_idx in start.y..=end_y {
+            if line_idx > start.y {
+                let joint = match view.text.lines.get(line_idx) {
+                    Some(FmtLine::TableRow(_)) | None => None,
+                    Some(line) => mark::line_composites(line)
+                        .first()
+                        .and_then(|fc| fc.wrap_joint),
+                };
+                match joint {
+                    Some(joint) => s.push_str(joint.as_str()),
+                    None => s.push('\n'),
+                }
+            }
+            let mut cell = None;
+            for c in Self::selected_chars(view, bounds, line_idx) {
+                if cell.map_or(false, |cell| cell != c.cell) {
+                    s.push('\t');
+                }
+                cell = Some(c.cell);
+                s.push(c.ch);
+            }
+        }
+        Some(s)
+    }
+    /// Return a copy of the line with the selected part marked, or
+    /// None if nothing is selected in this line
+    pub(crate) fn marked_line<'s>(
+        &self,
+        view: &TextView<'_, '_>,
+        line_idx: usize,
+        line: &FmtLine<'s>,
+    ) -> Option<FmtLine<'s>> {
+        let bounds = self.bounds(view)?;
+        let chars = Self::selected_chars(view, bounds, line_idx);
+        if chars.is_empty() {
+            return None;
+        }
+        let mut line = line.clone();
+        let mut idx = 0;
+        while idx < chars.len() {
+            let first = chars[idx];
+            let mut last = first;
+            while idx + 1 < chars.len() && chars[idx + 1].cell == first.cell {
+                idx += 1;
+                last = chars[idx];
+            }
+            if let Some(fc) = mark::line_composite_mut(&mut line, first.cell) {
+                let end = last.offset + last.ch.len_utf8();
+                mark::mark_range(fc, first.offset, end, MarkKind::Selection);
+            }
+            idx += 1;
+        }
+        Some(line)
+    }
+}
+
+impl TextView<'_, '_> {
+    /// return the chars of the content of a line of the text
+    fn line_chars(&self, line_idx: usize) -> Vec<LineChar> {
+        match self.text.lines.get(line_idx) {
+            Some(line) => line_chars(self.text.skin, line, self.content_width()),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod text_selection_tests {
+    use {
+        super::*,
+        crate::*,
+        pretty_assertions::assert_eq,
+    };
+
+    fn mouse(kind: MouseEventKind, x: u16, y: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column: x,
+            row: y,
+            modifiers: crate::crossterm::event::KeyModifiers::NONE,
+        }
+    }
+
+    static MD: &str = r#"
+# Title
+* some **bold** item
+> a quote
+|a|b|
+|-|-|
+|cell one|two|
+"#;
+
+    #[test]
+    fn test_drag_selection() {
+        let skin = MadSkin::no_style();
+        let area = Area::new(2, 1, 20, 10);
+        let text = skin.area_text(MD, &area);
+        let view = TextView::from(&area, &text);
+        let mut selection = TextSelection::new();
+        let left = MouseEventKind::Down(MouseButton::Left);
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        // from the 'b' of "bold" to the 'q' of "quote"
+        assert!(selection.apply_mouse_event(&view, mouse(left, 9, 3), false));
+        assert!(selection.is_empty());
+        assert!(selection.apply_mouse_event(&view, mouse(drag, 6, 4), false));
+        assert_eq!(selection.text(&view).unwrap(), "bold item\na q");
+        // in the table, the borders aren't copied
+        selection.apply_mouse_event(&view, mouse(left, 5, 7), false);
+        selection.apply_mouse_event(&view, mouse(drag, 13, 7), false);
+        assert_eq!(selection.text(&view).unwrap(), "ll one\ttw");
+        // the selection is painted with the skin's style
+        let mut skin = MadSkin::no_style();
+        skin.selection = CompoundStyle::with_attr(crossterm::style::Attribute::Reverse);
+        let text = skin.area_text(MD, &area);
+        let mut view = TextView::from(&area, &text);
+        view.selection = Some(&selection);
+        let mut buffer = Buffer::new(22, 11);
+        view.write_on(&mut buffer).unwrap();
+        let reversed: String = buffer
+            .row(7)
+            .iter()
+            .filter(|c| c.style.has_attr(crossterm::style::Attribute::Reverse))
+            .map(|c| c.ch)
+            .collect();
+        assert_eq!(reversed, "ll onetw");
+    }
+
+    #[test]
+    fn test_word_and_line_selection() {
+        let skin = MadSkin::no_style();
+        let area = Area::new(0, 0, 20, 10);
+        let text = skin.area_text(MD, &area);
+        let view = TextView::from(&area, &text);
+        let mut selection = TextSelection::new();
+        let script = EventScript::new()
+            .double_click(8, 2)
+            .wait_millis(100)
+            .click(8, 2)
+            .wait_millis(1000)
+            .click(3, 2);
+        let event_source = EventSource::with_script(script, EventSourceOptions::default());
+        let rx = event_source.receiver();
+        let mut texts = Vec::new();
+        while let Ok(timed_event) = rx.recv() {
+            if selection.apply_timed_event(&view, &timed_event) {
+                texts.push(selection.text(&view));
+            }
+            event_source.unblock(false);
+        }
+        let texts: Vec<Option<&str>> = texts.iter().map(|t| t.as_deref()).collect();
+        assert_eq!(
+            texts,
+            vec![None, Some("bold"), Some("some bold item"), None],
+        );
+    }
+
+    #[test]
+    fn test_selection_across_wraps() {
+        let skin = MadSkin::no_style();
+        let area = Area::new(0, 0, 16, 5);
+        let text = skin.area_text("* an item wrapped over two lines\nnext", &area);
+        let view = TextView::from(&area, &text);
+        let mut selection = TextSelection::new();
+        let left = MouseEventKind::Down(MouseButton::Left);
+        let drag = MouseEventKind::Drag(MouseButton::Left);
+        selection.apply_mouse_event(&view, mouse(left, 5, 0), false);
+        selection.apply_mouse_event(&view, mouse(drag, 0, 3), false);
+        assert_eq!(selection.text(&view).unwrap(), "item wrapped over two lines\nn");
+    }
+}
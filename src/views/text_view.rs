@@ -14,7 +14,12 @@ use {
         },
         displayable_line::DisplayableLine,
         errors::Result,
+        search::TextSearch,
         text::FmtText,
+        views::{
+            Pos,
+            TextSelection,
+        },
         SPACE_FILLING,
     },
     crokey::{
@@ -57,9 +62,13 @@ use {
 /// is computed accordingly to the area.
 pub struct TextView<'a, 't> {
     area: &'a Area,
-    text: &'t FmtText<'t, 't>,
+    pub(super) text: &'t FmtText<'t, 't>,
     pub scroll: usize, // number of lines hidden at start
     pub show_scrollbar: bool,
+    /// a search whose matches are highlighted
+    pub search: Option<&'a TextSearch>,
+    /// a selection to paint
+    pub selection: Option<&'a TextSelection>,
 }
 
 impl<'a, 't> TextView<'a, 't> {
@@ -70,6 +79,8 @@ impl<'a, 't> TextView<'a, 't> {
             text,
             scroll: 0,
             show_scrollbar: true,
+            search: None,
+            selection: None,
         }
     }
 
@@ -90,6 +101,41 @@ impl<'a, 't> TextView<'a, 't> {
         }
     }
 
+    /// return the width available for the text, that is the
+    /// width of the area minus the scrollbar, if any
+    pub fn content_width(&self) -> usize {
+        let width = self.area.width as usize;
+        if self.scrollbar().is_some() {
+            width.saturating_sub(1)
+        } else {
+            width
+        }
+    }
+
+    /// Return the position in the text (y being the line index and x
+    /// the column) of a screen position, if it's in the area
+    pub fn text_pos(&self, column: u16, row: u16) -> Option<Pos> {
+        if !self.area.contains(column, row) {
+            return None;
+        }
+        Some(Pos::new(
+            (column - self.area.left) as usize,
+            self.scroll + (row - self.area.top) as usize,
+        ))
+    }
+
+    /// Return the position in the text of a screen position, moved
+    /// into the area if it's outside
+    pub fn clamped_text_pos(&self, column: u16, row: u16) -> Pos {
+        let area = self.area;
+        let column = column.clamp(area.left, (area.left + area.width).max(area.left + 1) - 1);
+        let row = row.clamp(area.top, (area.top + area.height).max(area.top + 1) - 1);
+        Pos::new(
+            (column - area.left) as usize,
+            self.scroll + (row - area.top) as usize,
+        )
+    }
+
     /// display the text in the area, taking the scroll into account.
     pub fn write(&self) -> Result<()> {
         let mut stdout = stdout();
@@ -101,15 +147,22 @@ impl<'a, 't> TextView<'a, 't> {
     /// display the text in the area, taking the scroll into account.
     pub fn write_on<W: Write>(&self, w: &mut W) -> Result<()> {
         let scrollbar = self.scrollbar();
-        let mut lines = self.text.lines.iter().skip(self.scroll);
-        let mut width = self.area.width as usize;
-        if scrollbar.is_some() {
-            width -= 1;
-        }
+        let mut lines = self.text.lines.iter().enumerate().skip(self.scroll);
+        let width = self.content_width();
         for j in 0..self.area.height {
             let y = self.area.top + j;
             w.queue(MoveTo(self.area.left, y))?;
-            if let Some(line) = lines.next() {
+            if let Some((line_idx, line)) = lines.next() {
+                let mut marked = self
+                    .search
+                    .and_then(|search| search.highlighted_line(line_idx, line));
+                if let Some(selection) = self.selection {
+                    let line = marked.as_ref().unwrap_or(line);
+                    if let Some(line) = selection.marked_line(self, line_idx, line) {
+                        marked = Some(line);
+                    }
+                }
+                let line = marked.as_ref().unwrap_or(line);
                 let dl = DisplayableLine::new(self.text.skin, line, Some(width));
                 queue!(w, Print(&dl))?;
             } else {
@@ -163,6 +216,31 @@ impl<'a, 't> TextView<'a, 't> {
         self.try_scroll_lines(lines as i32);
     }
 
+    /// Change the scroll, if necessary, to make the given line visible.
+    ///
+    /// Return true when the scroll changed.
+    pub fn show_line(&mut self, line: usize) -> bool {
+        let old_scroll = self.scroll;
+        let area_height = (self.area.height as usize).max(1);
+        if line < self.scroll {
+            self.set_scroll(line);
+        } else if line >= self.scroll + area_height {
+            self.set_scroll(line + 1 - area_height);
+        }
+        self.scroll != old_scroll
+    }
+
+    /// Change the scroll, if necessary, to make the current
+    /// match of the search visible.
+    ///
+    /// Return true when the scroll changed.
+    pub fn show_search_match(&mut self, search: &TextSearch) -> bool {
+        match search.current() {
+            Some(m) => self.show_line(m.line),
+            None => false,
+        }
+    }
+
     pub fn line_up(&mut self) -> bool {
         if self.scroll > 0 {
             self.scroll -= 1;